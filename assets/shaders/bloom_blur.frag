@@ -0,0 +1,20 @@
+#version 100
+precision mediump float;
+varying vec2 uv;
+uniform sampler2D Texture;
+uniform float DirX;
+uniform float DirY;
+
+void main() {
+    vec2 dir = vec2(DirX, DirY);
+    vec3 sum = texture2D(Texture, uv).rgb * 0.227027;
+    sum += texture2D(Texture, uv + dir * 1.0).rgb * 0.194595;
+    sum += texture2D(Texture, uv - dir * 1.0).rgb * 0.194595;
+    sum += texture2D(Texture, uv + dir * 2.0).rgb * 0.121622;
+    sum += texture2D(Texture, uv - dir * 2.0).rgb * 0.121622;
+    sum += texture2D(Texture, uv + dir * 3.0).rgb * 0.054054;
+    sum += texture2D(Texture, uv - dir * 3.0).rgb * 0.054054;
+    sum += texture2D(Texture, uv + dir * 4.0).rgb * 0.016216;
+    sum += texture2D(Texture, uv - dir * 4.0).rgb * 0.016216;
+    gl_FragColor = vec4(sum, 1.0);
+}
@@ -0,0 +1,19 @@
+#version 100
+precision mediump float;
+varying vec2 uv;
+uniform sampler2D Texture;
+uniform sampler2D Glow;
+uniform sampler2D History;
+uniform float Intensity;
+uniform float Persistence;
+
+void main() {
+    vec4 color = texture2D(Texture, uv);
+    color.rgb += texture2D(Glow, uv).rgb * Intensity;
+
+    // Phosphor afterglow: last frame's image, faded, shows wherever it's still brighter.
+    // With Persistence at 0.0 this does nothing.
+    vec3 history = texture2D(History, uv).rgb * Persistence;
+    color.rgb = max(color.rgb, history);
+    gl_FragColor = color;
+}
@@ -0,0 +1,13 @@
+#version 100
+precision mediump float;
+varying vec2 uv;
+uniform sampler2D Texture;
+uniform float Threshold;
+
+void main() {
+    vec3 color = texture2D(Texture, uv).rgb;
+    // Soft knee: fades in over the range above Threshold instead of cutting off hard
+    float luma = dot(color, vec3(0.299, 0.587, 0.114));
+    float weight = clamp((luma - Threshold) / max(1.0 - Threshold, 0.001), 0.0, 1.0);
+    gl_FragColor = vec4(color * weight, 1.0);
+}
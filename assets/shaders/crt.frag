@@ -0,0 +1,58 @@
+#version 100
+precision lowp float;
+varying vec2 uv;
+uniform sampler2D Texture;
+
+// Tunables, set from CrtSettings every frame (see CrtPass::apply)
+uniform float Curvature;
+uniform float Aberration;
+uniform float ScanlineFrequency;
+uniform float ScanlineStrength;
+uniform float VignetteExponent;
+uniform float Brightness;
+
+// Bends UV coords to simulate a curved CRT tube.
+// Raise Curvature for more extreme bending; 0.0 keeps the screen flat.
+vec2 curve(vec2 uv) {
+    uv = (uv - 0.5) * 2.0;
+    uv *= 1.0 + dot(uv.yx, uv.yx) * Curvature;
+    uv = (uv / 2.0) + 0.5;
+    return uv;
+}
+
+void main() {
+    vec2 curved_uv = curve(uv);
+
+    // Anything outside 0..1 after curving is the black border around the screen
+    if (curved_uv.x < 0.0 || curved_uv.x > 1.0 ||
+        curved_uv.y < 0.0 || curved_uv.y > 1.0) {
+        gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    // Chromatic aberration: sample R/G/B at slightly different UV offsets.
+    // Mimics the color misalignment of old CRT phosphor guns.
+    float r = texture2D(Texture, curved_uv + vec2(Aberration, 0.0)).r;
+    float g = texture2D(Texture, curved_uv).g;
+    float b = texture2D(Texture, curved_uv - vec2(Aberration, 0.0)).b;
+    float a = texture2D(Texture, curved_uv).a;
+    vec4 color = vec4(r, g, b, a);
+
+    // Scanlines: creates horizontal dark bands like a real CRT.
+    // ScanlineFrequency controls line density, ScanlineStrength their darkness.
+    float scanline = sin(curved_uv.y * ScanlineFrequency) * ScanlineStrength;
+    color.rgb -= scanline;
+
+    // Vignette: darkens screen edges, brighter in the center.
+    // A higher exponent gives a stronger, wider dark border; 0.0 turns it off.
+    // (max() avoids pow(0, 0), which GLSL leaves undefined, in the corners.)
+    float vignette = 16.0 * curved_uv.x * curved_uv.y *
+                     (1.0 - curved_uv.x) * (1.0 - curved_uv.y);
+    vignette = clamp(pow(max(vignette, 0.0001), VignetteExponent), 0.0, 1.0);
+    color.rgb *= vignette;
+
+    // Brightness boost to compensate for the overall darkening
+    color.rgb *= Brightness;
+
+    gl_FragColor = color;
+}
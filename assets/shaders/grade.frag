@@ -0,0 +1,23 @@
+#version 100
+precision lowp float;
+varying vec2 uv;
+uniform sampler2D Texture;
+uniform float Contrast;
+uniform float Saturation;
+uniform float TintR;
+uniform float TintG;
+uniform float TintB;
+
+void main() {
+    vec4 color = texture2D(Texture, uv);
+
+    // Saturation: blend between greyscale (0.0) and the original color (1.0)
+    float luma = dot(color.rgb, vec3(0.299, 0.587, 0.114));
+    color.rgb = mix(vec3(luma), color.rgb, Saturation);
+
+    // Contrast around mid-grey
+    color.rgb = (color.rgb - 0.5) * Contrast + 0.5;
+
+    color.rgb *= vec3(TintR, TintG, TintB);
+    gl_FragColor = color;
+}
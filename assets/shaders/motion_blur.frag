@@ -0,0 +1,10 @@
+#version 100
+precision lowp float;
+varying vec2 uv;
+uniform sampler2D Texture;
+uniform sampler2D History;
+uniform float Amount;
+
+void main() {
+    gl_FragColor = mix(texture2D(Texture, uv), texture2D(History, uv), Amount);
+}
@@ -0,0 +1,14 @@
+#version 100
+precision mediump float;
+varying vec2 uv;
+uniform sampler2D Texture;
+uniform float PixelSize;
+uniform float Width;
+uniform float Height;
+
+void main() {
+    // Snap UVs to the centre of a PixelSize x PixelSize block
+    vec2 block = vec2(PixelSize / Width, PixelSize / Height);
+    vec2 snapped = (floor(uv / block) + 0.5) * block;
+    gl_FragColor = texture2D(Texture, snapped);
+}
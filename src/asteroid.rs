@@ -1,6 +1,8 @@
 use macroquad::prelude::*;
+use crate::torus;
+use macroquad::rand::RandGenerator;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum AsteroidSize {
     Big,
     Medium,
@@ -56,68 +58,80 @@ pub struct Asteroid {
     pub rotation: f32,
     pub rot_speed: f32,
     pub size: AsteroidSize,
-    pub texture: Texture2D,
     pub alive: bool,
+
+    // Wave difficulty multiplier on size.speed(). Passed on to split children.
+    pub speed_scale: f32,
+
+    // State at the end of the previous simulation step, used for interpolation.
+    pub prev_pos: Vec2,
+    pub prev_rotation: f32,
 }
 
 impl Asteroid {
-    pub fn new(pos: Vec2, size: AsteroidSize, texture: Texture2D) -> Self {
-        let angle = rand::gen_range(0.0_f32, std::f32::consts::TAU);
-        let speed = size.speed();
-        let rot_speed = rand::gen_range(-2.0_f32, 2.0_f32);
+    // Random heading, spin and starting angle all come from `rng`,
+    // so the same generator state always produces the same asteroid.
+    pub fn new(pos: Vec2, size: AsteroidSize, speed_scale: f32, rng: &RandGenerator) -> Self {
+        let angle = rng.gen_range(0.0_f32, std::f32::consts::TAU);
+        let speed = size.speed() * speed_scale;
+        let rot_speed = rng.gen_range(-2.0_f32, 2.0_f32);
+        let rotation = rng.gen_range(0.0_f32, std::f32::consts::TAU);
 
         Self {
             pos,
             vel: Vec2::from_angle(angle) * speed,
-            rotation: rand::gen_range(0.0_f32, std::f32::consts::TAU),
+            rotation,
             rot_speed,
             size,
-            texture,
             alive: true,
+            speed_scale,
+            prev_pos: pos,
+            prev_rotation: rotation,
         }
     }
 
     /// Spawn two children after being hit
-    pub fn split(&self, tex_medium: &Texture2D, tex_small: &Texture2D) -> Vec<Asteroid> {
+    pub fn split(&self, rng: &RandGenerator) -> Vec<Asteroid> {
         let child_size = match self.size.split() {
             Some(s) => s,
             None => return vec![],
         };
-        let tex = match child_size {
-            AsteroidSize::Medium => tex_medium.clone(),
-            AsteroidSize::Small  => tex_small.clone(),
-            _ => unreachable!(),
-        };
 
-        (0..2).map(|_| Asteroid::new(self.pos, child_size, tex.clone())).collect()
+        (0..2).map(|_| Asteroid::new(self.pos, child_size, self.speed_scale, rng)).collect()
     }
 
-    pub fn update(&mut self) {
-        let dt = get_frame_time();
+    // Advances the asteroid by one fixed simulation step of `dt` seconds,
+    // wrapping around a playfield of size `bounds`.
+    pub fn update(&mut self, dt: f32, bounds: Vec2) {
+        self.prev_pos = self.pos;
+        self.prev_rotation = self.rotation;
         self.rotation += self.rot_speed * dt;
         self.pos += self.vel * dt;
 
-        let (w, h) = (screen_width(), screen_height());
-        if self.pos.x < 0.0 { self.pos.x = w; }
-        if self.pos.x > w   { self.pos.x = 0.0; }
-        if self.pos.y < 0.0 { self.pos.y = h; }
-        if self.pos.y > h   { self.pos.y = 0.0; }
+        let before = self.pos;
+        self.pos = torus::wrap(self.pos, bounds);
+        self.prev_pos += self.pos - before;
     }
 
-    pub fn draw(&self) {
+    // Near an edge the asteroid is also drawn on the opposite side, so it slides across.
+    pub fn draw(&self, texture: &Texture2D, alpha: f32, bounds: Vec2) {
         let size = self.size.draw_size();
-        draw_texture_ex(
-            &self.texture,
-            self.pos.x - size / 2.0,
-            self.pos.y - size / 2.0,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(vec2(size, size)),
-                rotation: self.rotation,
-                pivot: Some(self.pos),
-                ..Default::default()
-            },
-        );
+        let pos = self.prev_pos.lerp(self.pos, alpha);
+        let rotation = self.prev_rotation + (self.rotation - self.prev_rotation) * alpha;
+        torus::for_each_ghost(pos, size / 2.0, bounds, |pos| {
+            draw_texture_ex(
+                texture,
+                pos.x - size / 2.0,
+                pos.y - size / 2.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(size, size)),
+                    rotation,
+                    pivot: Some(pos),
+                    ..Default::default()
+                },
+            );
+        });
     }
 
     pub fn radius(&self) -> f32 {
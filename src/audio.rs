@@ -0,0 +1,149 @@
+use crate::asteroid::AsteroidSize;
+use crate::synth;
+use macroquad::audio::{load_sound_from_bytes, play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound};
+
+// Sound effects and music.
+//
+// Every sound is generated at start-up by the synthesizer (synth.rs); there are no audio files.
+// One-shot effects are fired from world events (see Game::handle_events).
+// The thrust and saucer sounds loop for as long as Game says they should,
+// and the music loops from start-up. macroquad does the mixing, natively and in the browser.
+
+// One-shot sound effects
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Sfx {
+    Fire,
+    ExplosionBig,
+    ExplosionMedium,
+    ExplosionSmall,
+    ExtraLife,
+    GameOver,
+}
+
+// Sounds that play continuously while something is going on
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Loop {
+    Thrust,
+    Saucer,
+}
+
+// Volume knobs, all 0..1. Music and effects are each scaled by master.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Volumes {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for Volumes {
+    fn default() -> Self {
+        Self { master: 0.8, music: 0.5, sfx: 1.0 }
+    }
+}
+
+pub struct Audio {
+    fire: Sound,
+    explosion_big: Sound,
+    explosion_medium: Sound,
+    explosion_small: Sound,
+    extra_life: Sound,
+    game_over: Sound,
+    thrust: Sound,
+    saucer: Sound,
+    music: Sound,
+
+    // Whether each loop is currently playing, so we only start/stop on changes
+    thrust_on: bool,
+    saucer_on: bool,
+
+    volumes: Volumes,
+}
+
+impl Audio {
+    // Renders every sound with the synthesizer and hands them to macroquad.
+    // Async because on WASM the browser decodes them in the background.
+    pub async fn load() -> Self {
+        Self {
+            fire:             Self::load_samples(&synth::render(&synth::fire())).await,
+            explosion_big:    Self::load_samples(&synth::render(&synth::explosion(AsteroidSize::Big))).await,
+            explosion_medium: Self::load_samples(&synth::render(&synth::explosion(AsteroidSize::Medium))).await,
+            explosion_small:  Self::load_samples(&synth::render(&synth::explosion(AsteroidSize::Small))).await,
+            extra_life:       Self::load_samples(&synth::extra_life()).await,
+            game_over:        Self::load_samples(&synth::game_over()).await,
+            thrust:           Self::load_samples(&synth::render(&synth::thrust())).await,
+            saucer:           Self::load_samples(&synth::render(&synth::saucer())).await,
+            music:            Self::load_samples(&synth::music()).await,
+            thrust_on: false,
+            saucer_on: false,
+            volumes: Volumes::default(),
+        }
+    }
+
+    async fn load_samples(samples: &[f32]) -> Sound {
+        load_sound_from_bytes(&synth::to_wav(samples)).await.unwrap()
+    }
+
+    fn sfx_volume(&self) -> f32 {
+        self.volumes.master * self.volumes.sfx
+    }
+
+    fn music_volume(&self) -> f32 {
+        self.volumes.master * self.volumes.music
+    }
+
+    // Plays a one-shot effect. `scale` makes it quieter (or louder) than usual.
+    pub fn play(&self, sfx: Sfx, scale: f32) {
+        let sound = match sfx {
+            Sfx::Fire            => &self.fire,
+            Sfx::ExplosionBig    => &self.explosion_big,
+            Sfx::ExplosionMedium => &self.explosion_medium,
+            Sfx::ExplosionSmall  => &self.explosion_small,
+            Sfx::ExtraLife       => &self.extra_life,
+            Sfx::GameOver        => &self.game_over,
+        };
+        play_sound(sound, PlaySoundParams { looped: false, volume: self.sfx_volume() * scale });
+    }
+
+    // Starts or stops a looping effect. Cheap to call every frame.
+    pub fn set_loop(&mut self, which: Loop, on: bool) {
+        let volume = self.sfx_volume();
+        let (sound, playing) = match which {
+            Loop::Thrust => (&self.thrust, &mut self.thrust_on),
+            Loop::Saucer => (&self.saucer, &mut self.saucer_on),
+        };
+        if on == *playing {
+            return;
+        }
+        *playing = on;
+        if on {
+            play_sound(sound, PlaySoundParams { looped: true, volume });
+        } else {
+            stop_sound(sound);
+        }
+    }
+
+    pub fn stop_loops(&mut self) {
+        self.set_loop(Loop::Thrust, false);
+        self.set_loop(Loop::Saucer, false);
+    }
+
+    pub fn start_music(&self) {
+        play_sound(&self.music, PlaySoundParams { looped: true, volume: self.music_volume() });
+    }
+
+    pub fn volumes(&self) -> Volumes {
+        self.volumes
+    }
+
+    // Applies straight away to the music and any loops already playing.
+    pub fn set_volumes(&mut self, volumes: Volumes) {
+        self.volumes = Volumes {
+            master: volumes.master.clamp(0.0, 1.0),
+            music: volumes.music.clamp(0.0, 1.0),
+            sfx: volumes.sfx.clamp(0.0, 1.0),
+        };
+        set_sound_volume(&self.music, self.music_volume());
+        set_sound_volume(&self.thrust, self.sfx_volume());
+        set_sound_volume(&self.saucer, self.sfx_volume());
+    }
+}
@@ -0,0 +1,77 @@
+// Headless benchmark for the collision broadphase. Native only:
+//     cargo run --release -- --bench-broadphase
+//
+// Scatters thousands of asteroids and a few hundred bullets over a large wrapped
+// playfield, then times the bullet <-> asteroid check both ways: the old
+// every-bullet-against-every-asteroid loop, and SpatialHash rebuild + queries.
+// Both have to find the same number of hits, or the grid is missing something.
+
+use std::time::Instant;
+use macroquad::math::{vec2, Vec2};
+use macroquad::rand::RandGenerator;
+use crate::asteroid::{Asteroid, AsteroidSize};
+use crate::bullet::{Bullet, Faction};
+use crate::spatial::SpatialHash;
+use crate::torus;
+
+const BOUNDS: Vec2 = Vec2::new(5120.0, 2880.0);
+const BULLETS: usize = 500;
+const STEPS: u32 = 100;
+
+pub fn broadphase() {
+    println!("bullet <-> asteroid broadphase, {BULLETS} bullets, {STEPS} steps, {}x{} field", BOUNDS.x, BOUNDS.y);
+    println!("{:>10} {:>14} {:>14} {:>9}", "asteroids", "naive ms/step", "grid ms/step", "speedup");
+
+    for count in [500, 1_000, 2_000, 5_000, 10_000] {
+        let rng = RandGenerator::new();
+        rng.srand(count as u64);
+
+        let sizes = [AsteroidSize::Big, AsteroidSize::Medium, AsteroidSize::Small];
+        let asteroids: Vec<Asteroid> = (0..count)
+            .map(|i| Asteroid::new(random_pos(&rng), sizes[i % 3], 1.0, &rng))
+            .collect();
+        let bullets: Vec<Bullet> = (0..BULLETS)
+            .map(|_| Bullet::new(random_pos(&rng), Vec2::X, Faction::Player))
+            .collect();
+
+        // Naive: every pair
+        let start = Instant::now();
+        let mut naive_hits = 0;
+        for _ in 0..STEPS {
+            for b in &bullets {
+                for a in &asteroids {
+                    if torus::distance(b.pos, a.pos, BOUNDS) < b.radius() + a.radius() {
+                        naive_hits += 1;
+                    }
+                }
+            }
+        }
+        let naive = start.elapsed().as_secs_f64() * 1000.0 / STEPS as f64;
+
+        // Grid: rebuilt every step, like World::step does
+        let mut grid = SpatialHash::new(AsteroidSize::Big.radius() * 2.0);
+        let mut candidates = vec![];
+        let start = Instant::now();
+        let mut grid_hits = 0;
+        for _ in 0..STEPS {
+            grid.rebuild(BOUNDS, asteroids.iter().enumerate().map(|(i, a)| (i, a.pos, a.radius())));
+            for b in &bullets {
+                grid.query(b.pos, b.radius(), &mut candidates);
+                for &i in &candidates {
+                    let a = &asteroids[i];
+                    if torus::distance(b.pos, a.pos, BOUNDS) < b.radius() + a.radius() {
+                        grid_hits += 1;
+                    }
+                }
+            }
+        }
+        let gridded = start.elapsed().as_secs_f64() * 1000.0 / STEPS as f64;
+
+        assert_eq!(naive_hits, grid_hits, "broadphase missed collisions with {count} asteroids");
+        println!("{count:>10} {naive:>14.3} {gridded:>14.3} {:>8.1}x", naive / gridded);
+    }
+}
+
+fn random_pos(rng: &RandGenerator) -> Vec2 {
+    vec2(rng.gen_range(0.0, BOUNDS.x), rng.gen_range(0.0, BOUNDS.y))
+}
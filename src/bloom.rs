@@ -0,0 +1,106 @@
+use std::any::Any;
+use macroquad::prelude::*;
+use crate::postfx::{blit, create_target, PassShader, PostPass};
+
+// Bloom / phosphor glow, as one pass of the post-processing chain (see postfx.rs).
+//
+// 1. Bright extract: keep only what's brighter than `threshold`, at half resolution
+// 2. Separable Gaussian blur: horizontal, then vertical, still at half resolution
+// 3. Composite: add the blurred glow back on top of the original image
+//
+// Optional afterglow: the composite also blends in the previous frame, fading by
+// `persistence` every frame, so fast things leave a short trail like phosphor does.
+
+const EXTRACT_FRAGMENT: &str = include_str!("../assets/shaders/bloom_extract.frag");
+
+// 9-tap Gaussian along one axis. DirX/DirY are one texel step in UV units
+// (times the spread), so the same shader does both the horizontal and vertical pass.
+const BLUR_FRAGMENT: &str = include_str!("../assets/shaders/bloom_blur.frag");
+
+const COMPOSITE_FRAGMENT: &str = include_str!("../assets/shaders/bloom_composite.frag");
+
+pub struct BloomPass {
+    // Brightness (0..1) above which things start to glow
+    pub threshold: f32,
+    // How strongly the glow is added back
+    pub intensity: f32,
+    // Blur sample spacing in half-res texels; higher = wider, softer glow
+    pub spread: f32,
+
+    // Phosphor afterglow mode, and how much of the last frame survives each frame
+    pub afterglow: bool,
+    pub persistence: f32,
+
+    extract: PassShader,
+    blur: PassShader,
+    composite: PassShader,
+
+    // Half-resolution targets the blur ping-pongs between
+    half: [RenderTarget; 2],
+    // Last frame's output, for the afterglow
+    history: RenderTarget,
+}
+
+impl BloomPass {
+    pub fn new() -> Self {
+        let one = vec2(1.0, 1.0);
+        Self {
+            threshold: 0.6,
+            intensity: 1.2,
+            spread: 1.5,
+            afterglow: false,
+            persistence: 0.75,
+            extract: PassShader::new("bloom_extract.frag", EXTRACT_FRAGMENT, &["Threshold"], &[]),
+            blur: PassShader::new("bloom_blur.frag", BLUR_FRAGMENT, &["DirX", "DirY"], &[]),
+            composite: PassShader::new("bloom_composite.frag", COMPOSITE_FRAGMENT, &["Intensity", "Persistence"], &["Glow", "History"]),
+            half: [create_target(one), create_target(one)],
+            history: create_target(one),
+        }
+    }
+}
+
+impl PostPass for BloomPass {
+    fn name(&self) -> &'static str { "Bloom" }
+
+    fn apply(&self, input: &Texture2D, output: &RenderTarget, size: Vec2) {
+        let half_size = self.half[0].texture.size();
+
+        // Bright extract, downsampled on the way
+        self.extract.material().set_uniform("Threshold", self.threshold);
+        blit(input, &self.half[0], half_size, Some(self.extract.material()));
+
+        // Blur: horizontal into half[1], vertical back into half[0]
+        self.blur.material().set_uniform("DirX", self.spread / half_size.x);
+        self.blur.material().set_uniform("DirY", 0.0_f32);
+        blit(&self.half[0].texture, &self.half[1], half_size, Some(self.blur.material()));
+        self.blur.material().set_uniform("DirX", 0.0_f32);
+        self.blur.material().set_uniform("DirY", self.spread / half_size.y);
+        blit(&self.half[1].texture, &self.half[0], half_size, Some(self.blur.material()));
+
+        // Composite at full resolution
+        let persistence = if self.afterglow { self.persistence.clamp(0.0, 0.95) } else { 0.0 };
+        self.composite.material().set_uniform("Intensity", self.intensity);
+        self.composite.material().set_uniform("Persistence", persistence);
+        self.composite.material().set_texture("Glow", self.half[0].texture.clone());
+        self.composite.material().set_texture("History", self.history.texture.clone());
+        blit(input, output, size, Some(self.composite.material()));
+
+        // Remember this frame for the next one. Kept up to date even with the afterglow
+        // off, so switching it on doesn't flash a stale frame.
+        blit(&output.texture, &self.history, size, None);
+    }
+
+    fn resize(&mut self, size: Vec2) {
+        let half_size = (size / 2.0).floor().max(vec2(1.0, 1.0));
+        self.half = [create_target(half_size), create_target(half_size)];
+        self.history = create_target(size);
+    }
+
+    fn shaders_mut(&mut self) -> Vec<&mut PassShader> {
+        vec![&mut self.extract, &mut self.blur, &mut self.composite]
+    }
+
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -1,26 +1,41 @@
 use macroquad::prelude::*;
+use crate::torus;
+
+// Who fired a bullet. Decides what it can hit:
+// player bullets hit asteroids and saucers, saucer bullets hit asteroids and the player.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Faction {
+    Player,
+    Saucer,
+}
 
 pub struct Bullet {
     pub pos: Vec2,
     pub vel: Vec2,
-    pub texture: Texture2D,
+    pub faction: Faction,
     pub alive: bool,
     lifetime: f32,
+
+    // Position at the end of the previous simulation step, used for interpolation.
+    pub prev_pos: Vec2,
 }
 
 impl Bullet {
-    pub fn new(pos: Vec2, direction: Vec2, texture: Texture2D) -> Self {
+    pub fn new(pos: Vec2, direction: Vec2, faction: Faction) -> Self {
         Self {
             pos,
             vel: direction * 600.0,
-            texture,
+            faction,
             alive: true,
             lifetime: 1.5,
+            prev_pos: pos,
         }
     }
 
-    pub fn update(&mut self) {
-        let dt = get_frame_time();
+    // Advances the bullet by one fixed simulation step of `dt` seconds,
+    // wrapping around a playfield of size `bounds`.
+    pub fn update(&mut self, dt: f32, bounds: Vec2) {
+        self.prev_pos = self.pos;
         self.lifetime -= dt;
         if self.lifetime <= 0.0 {
             self.alive = false;
@@ -29,26 +44,33 @@ impl Bullet {
 
         self.pos += self.vel * dt;
 
-        // Screen wrap
-        let (w, h) = (screen_width(), screen_height());
-        if self.pos.x < 0.0 { self.pos.x = w; }
-        if self.pos.x > w   { self.pos.x = 0.0; }
-        if self.pos.y < 0.0 { self.pos.y = h; }
-        if self.pos.y > h   { self.pos.y = 0.0; }
+        // Screen wrap (prev_pos follows so interpolation doesn't streak across the screen)
+        let before = self.pos;
+        self.pos = torus::wrap(self.pos, bounds);
+        self.prev_pos += self.pos - before;
     }
 
-    pub fn draw(&self) {
+    pub fn draw(&self, texture: &Texture2D, alpha: f32, bounds: Vec2) {
         let size = 8.0;
-        draw_texture_ex(
-            &self.texture,
-            self.pos.x - size / 2.0,
-            self.pos.y - size / 2.0,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(vec2(size, size)),
-                ..Default::default()
-            },
-        );
+        let pos = self.prev_pos.lerp(self.pos, alpha);
+
+        // Saucer shots are tinted so they're easy to tell apart
+        let tint = match self.faction {
+            Faction::Player => WHITE,
+            Faction::Saucer => Color::new(1.0, 0.35, 0.35, 1.0),
+        };
+        torus::for_each_ghost(pos, size / 2.0, bounds, |pos| {
+            draw_texture_ex(
+                texture,
+                pos.x - size / 2.0,
+                pos.y - size / 2.0,
+                tint,
+                DrawTextureParams {
+                    dest_size: Some(vec2(size, size)),
+                    ..Default::default()
+                },
+            );
+        });
     }
 
     pub fn radius(&self) -> f32 { 4.0 }
@@ -0,0 +1,206 @@
+use macroquad::prelude::*;
+use crate::gamepad::Button;
+use crate::storage;
+
+// What the player can do in a run, and which keys do it.
+//
+// The game asks "is Thrust down?" rather than "is W down?", so any action can be
+// moved to other keys (left-handed layouts, AZERTY, ...). Custom bindings are saved
+// as plain text, one action per line, and anything missing falls back to the default:
+//     thrust Up W
+//     fire Space Z
+//
+// Menus are not part of this: arrows, Enter and Escape always work there, and
+// Escape always pauses a run, so the way back out can never be rebound away.
+//
+// Gamepad buttons map to the same actions (see pad_buttons), on top of the stick
+// and trigger handling in Game::read_input. Those aren't rebindable.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    RotateLeft,
+    RotateRight,
+    Thrust,
+    Fire,
+    Hyperspace,
+    Pause,
+    Restart,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::Thrust,
+        Action::Fire,
+        Action::Hyperspace,
+        Action::Pause,
+        Action::Restart,
+    ];
+
+    // For the rebinding screen
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::RotateLeft  => "Rotate left",
+            Action::RotateRight => "Rotate right",
+            Action::Thrust      => "Thrust",
+            Action::Fire        => "Fire",
+            Action::Hyperspace  => "Hyperspace",
+            Action::Pause       => "Pause",
+            Action::Restart     => "Restart",
+        }
+    }
+
+    // For the saved file
+    fn id(self) -> &'static str {
+        match self {
+            Action::RotateLeft  => "rotate_left",
+            Action::RotateRight => "rotate_right",
+            Action::Thrust      => "thrust",
+            Action::Fire        => "fire",
+            Action::Hyperspace  => "hyperspace",
+            Action::Pause       => "pause",
+            Action::Restart     => "restart",
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&a| a == self).unwrap()
+    }
+}
+
+// Gamepad buttons for each action
+pub fn pad_buttons(action: Action) -> &'static [Button] {
+    match action {
+        Action::RotateLeft  => &[Button::DPadLeft],
+        Action::RotateRight => &[Button::DPadRight],
+        Action::Thrust      => &[Button::DPadUp],
+        Action::Fire        => &[Button::South, Button::RightShoulder],
+        Action::Hyperspace  => &[Button::East, Button::LeftShoulder],
+        Action::Pause       => &[Button::Start],
+        Action::Restart     => &[Button::Select],
+    }
+}
+
+// Keys per action. Binding another one drops the oldest.
+pub const MAX_KEYS: usize = 3;
+
+const STORAGE_KEY: &str = "controls";
+
+// Keys an action can be bound to. Left out: Escape and Enter (menus), -/= and the
+// F keys (volume and effect hotkeys), and the odd ones not every keyboard has.
+const BINDABLE: [KeyCode; 80] = [
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G,
+    KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+    KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U,
+    KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Space, KeyCode::Tab, KeyCode::Backspace,
+    KeyCode::Insert, KeyCode::Delete, KeyCode::Home, KeyCode::End, KeyCode::PageUp, KeyCode::PageDown,
+    KeyCode::LeftShift, KeyCode::RightShift, KeyCode::LeftControl, KeyCode::RightControl,
+    KeyCode::LeftAlt, KeyCode::RightAlt,
+    KeyCode::Apostrophe, KeyCode::Comma, KeyCode::Period, KeyCode::Slash, KeyCode::Semicolon,
+    KeyCode::LeftBracket, KeyCode::RightBracket, KeyCode::Backslash, KeyCode::GraveAccent,
+    KeyCode::Kp0, KeyCode::Kp1, KeyCode::Kp2, KeyCode::Kp3, KeyCode::Kp4,
+    KeyCode::Kp5, KeyCode::Kp6, KeyCode::Kp7, KeyCode::Kp8, KeyCode::Kp9,
+    KeyCode::KpDecimal, KeyCode::KpDivide, KeyCode::KpMultiply, KeyCode::KpSubtract, KeyCode::KpAdd,
+    KeyCode::KpEqual,
+];
+
+pub fn is_bindable(key: KeyCode) -> bool {
+    BINDABLE.contains(&key)
+}
+
+// How a key is written in the saved file: its KeyCode variant name
+fn key_id(key: KeyCode) -> String {
+    format!("{key:?}")
+}
+
+fn parse_key(id: &str) -> Option<KeyCode> {
+    BINDABLE.iter().copied().find(|&key| key_id(key) == id)
+}
+
+// How a key is shown on screen ("1" rather than "Key1")
+pub fn key_name(key: KeyCode) -> String {
+    let id = key_id(key);
+    match id.strip_prefix("Key") {
+        Some(digit) => digit.to_string(),
+        None => id,
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bindings {
+    // Indexed by Action::index, most recently bound key first
+    keys: [Vec<KeyCode>; Action::ALL.len()],
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let keys = |action: Action| match action {
+            Action::RotateLeft  => vec![KeyCode::Left, KeyCode::A],
+            Action::RotateRight => vec![KeyCode::Right, KeyCode::D],
+            Action::Thrust      => vec![KeyCode::Up, KeyCode::W],
+            Action::Fire        => vec![KeyCode::Space, KeyCode::Z],
+            Action::Hyperspace  => vec![KeyCode::LeftShift, KeyCode::RightShift, KeyCode::X],
+            Action::Pause       => vec![KeyCode::P],
+            Action::Restart     => vec![KeyCode::R],
+        };
+        Self { keys: Action::ALL.map(keys) }
+    }
+}
+
+impl Bindings {
+    // Defaults, with whatever was saved on top. Unknown actions and keys are skipped.
+    pub fn load() -> Self {
+        let mut bindings = Self::default();
+        for line in storage::load(STORAGE_KEY).unwrap_or_default().lines() {
+            let mut fields = line.split_whitespace();
+            let Some(id) = fields.next() else { continue };
+            let Some(action) = Action::ALL.into_iter().find(|a| a.id() == id) else { continue };
+            let keys: Vec<KeyCode> = fields.filter_map(parse_key).take(MAX_KEYS).collect();
+            bindings.keys[action.index()] = keys;
+        }
+        bindings
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let text: String = Action::ALL.iter()
+            .map(|&action| {
+                let keys: Vec<String> = self.keys(action).iter().map(|&k| key_id(k)).collect();
+                format!("{} {}\n", action.id(), keys.join(" "))
+            })
+            .collect();
+        storage::save(STORAGE_KEY, &text)
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        &self.keys[action.index()]
+    }
+
+    // Adds `key` to `action`. A key only ever does one thing, so it's taken off
+    // whatever action had it before.
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        for keys in self.keys.iter_mut() {
+            keys.retain(|&k| k != key);
+        }
+        let keys = &mut self.keys[action.index()];
+        keys.insert(0, key);
+        keys.truncate(MAX_KEYS);
+    }
+
+    // Whether some action uses `key`. Letter hotkeys (C, M, N, ...) stay out of the way if so.
+    pub fn is_bound(&self, key: KeyCode) -> bool {
+        self.keys.iter().any(|keys| keys.contains(&key))
+    }
+
+    pub fn down(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|&k| is_key_down(k))
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|&k| is_key_pressed(k))
+    }
+}
@@ -1,41 +1,229 @@
 use macroquad::prelude::*;
-use crate::player::Player;
-use crate::bullet::Bullet;
-use crate::asteroid::{Asteroid, AsteroidSize};
-use crate::shader::CrtEffect;
-
-// How many big asteroids spawn at the start of each game
-const INITIAL_ASTEROIDS: usize = 5;
-
-// Minimum distance from the player where asteroids can spawn.
-// Prevents instant death at game start.
-const SAFE_RADIUS: f32 = 150.0;
-
-// The game can be in one of these three states.
-// This drives what gets updated and what gets drawn.
-#[derive(PartialEq)]
-enum GameState {
-    Playing,
-    GameOver,
-    Victory,
+use macroquad::rand::RandGenerator;
+use crate::asteroid::AsteroidSize;
+use crate::bullet::Faction;
+use crate::controls::{self, Action, Bindings};
+use crate::gamepad::{self, Gamepad, VirtualGamepad};
+use crate::highscores::{self, HighScore, HighScores};
+use crate::launch::LaunchOptions;
+use crate::menu::{Menu, MenuEvent, MenuInput, MenuItem};
+use crate::replay::{Playback, Replay};
+use crate::rules::RuleSet;
+use crate::audio::{Audio, Loop, Sfx, Volumes};
+use crate::bloom::BloomPass;
+use crate::particles::ParticleSystem;
+use crate::player::TURN_SPEED;
+use crate::postfx::{ColorGradePass, MotionBlurPass, PixelatePass, PostChain};
+use crate::shake::ScreenShake;
+use crate::timestep::FixedStep;
+use crate::shader::{CrtPass, CrtPreset};
+use crate::world::{GameState, Input, World, WorldEvent};
+
+// The simulation always advances in fixed steps of this size, no matter the
+// monitor's refresh rate. 120 Hz keeps fast bullets from skipping over small asteroids.
+const SIM_HZ: f32 = 120.0;
+const SIM_DT: f32 = 1.0 / SIM_HZ;
+
+// Cap on how much real time a single frame may feed into the simulation.
+// After a long hitch (tab in background, window drag) we'd rather slow down
+// than run hundreds of catch-up steps in one frame.
+const MAX_FRAME_TIME: f32 = 0.25;
+
+// Size of the playfield in logical units. The simulation and all drawing use these
+// coordinates; PostChain scales the result to the window with black bars as needed.
+const WORLD_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+
+// How long short notices (e.g. "CRT: Subtle") stay on screen, in seconds
+const TOAST_TIME: f32 = 2.0;
+
+// How long the lives panel flashes after an extra life, in seconds
+const EXTRA_LIFE_FLASH: f32 = 1.5;
+
+// How long the game over menu ignores input, so fire mashed as the ship
+// goes down doesn't pick anything (South is fire and "pick" on a pad)
+const GAME_OVER_MENU_DELAY: f32 = 0.75;
+
+// Settings menu scanline tuning: one left/right press, and the most it goes up to
+// (the Extreme preset's strength)
+const SCANLINE_STEP: f32 = 0.03;
+const MAX_SCANLINES: f32 = 0.3;
+
+// Which screen the game is on. The World only knows about the run itself;
+// everything around it (title, menus, pause) lives here.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Screen {
+    // Title + main menu, with asteroids drifting behind
+    Title,
+    // The top-10 table
+    HighScores,
+    // Volume, CRT look and the comfort options. Reachable from the title and the pause menu.
+    Settings,
+    // Key bindings, reached from Settings
+    Controls,
+    // A run in progress, or just ended (see World::state)
+    Game,
+    // A run frozen under the pause menu
+    Paused,
+}
+
+// What kind of run Start begins
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Mode {
+    // A fresh seed every run (or the --seed one, if given)
+    Classic,
+    // The seed comes from today's date, so everyone gets the same asteroids all day
+    Daily,
+}
+
+impl Mode {
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Classic => "Classic",
+            Mode::Daily   => "Daily challenge",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Mode::Classic => Mode::Daily,
+            Mode::Daily   => Mode::Classic,
+        }
+    }
+
+    fn rule_set(self) -> RuleSet {
+        match self {
+            Mode::Classic => RuleSet::Classic,
+            Mode::Daily   => RuleSet::Daily,
+        }
+    }
 }
 
-// The central struct that owns everything in the game.
-// All textures, all entities, all state lives here.
+// Everything a menu row can do. Which rows a screen shows comes from Game::menu_items.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum MenuAction {
+    Play,
+    Mode,
+    Settings,
+    HighScores,
+    Quit,
+    Resume,
+    SaveReplay,
+    Title,
+    Volume,
+    Music,
+    Effects,
+    Crt,
+    Scanlines,
+    Bloom,
+    Shake,
+    HitStop,
+    StickAim,
+    Controls,
+    // Waits for a new key for this action
+    Bind(Action),
+    ResetControls,
+    Back,
+}
+
+// The macroquad side of the game: owns the simulation World plus everything
+// needed to drive it from real input and put it on screen.
 pub struct Game {
-    player: Player,
-    bullets: Vec<Bullet>,
-    asteroids: Vec<Asteroid>,
-    state: GameState,
-    score: u32,
+    // All entities, score and round state. See world.rs.
+    world: World,
+
+    // Seed the current world's RNG was created from.
+    // Same seed + same input = same game, so this is what you share to reproduce a run.
+    seed: u64,
+
+    // Seed given at launch (--seed / ?seed=). When set, every restart reuses it,
+    // e.g. for a daily-challenge seed. When None, each run gets a fresh one.
+    fixed_seed: Option<u64>,
+
+    // Every step's input of the current run, so it can be saved as a replay.
+    recording: Replay,
+
+    // When watching a replay, inputs come from here instead of the keyboard.
+    playback: Option<Playback>,
+
+    // Seconds left of the lives panel flash after earning an extra life
+    life_flash: f32,
+
+    // One-line message for the end-of-round overlay (e.g. where a replay was saved).
+    status: Option<String>,
+
+    // Turns real frame time into fixed SIM_DT steps, and runs the hit-stop
+    // (the simulation briefly freezing on a big hit). See timestep.rs.
+    clock: FixedStep,
+
+    // Fire presses are latched here once per rendered frame and consumed by the
+    // next simulation step. Otherwise a press could land on a frame that runs
+    // zero steps (high refresh rate) and be lost.
+    fire_queued: bool,
+
+    // Same latch for hyperspace jumps
+    hyperspace_queued: bool,
+
+    // Post-processing chain: the game is drawn off-screen, then run through
+    // these passes (CRT and friends) on its way to the window.
+    post: PostChain,
+    crt_preset: CrtPreset,
 
-    // The CRT post-processing effect.
-    // Wraps a render target + GLSL shader.
-    crt: CrtEffect,
+    // Compile errors of shader files that are currently broken (debug builds only,
+    // see PassShader). Shown on top of everything until the file is fixed.
+    shader_errors: Vec<String>,
 
-    // Textures are stored here and cloned (cheap, ref-counted) into entities.
+    // Debris, exhaust and explosions. Cosmetic only, fed by world events.
+    particles: ParticleSystem,
+
+    // Camera shake and hit-stop. Both can be switched off (F3 / F4).
+    shake: ScreenShake,
+    hit_stop_enabled: bool,
+
+    audio: Audio,
+
+    // Whether the ship was thrusting in the last simulation step (drives the thrust loop)
+    thrusting: bool,
+
+    screen: Screen,
+
+    // Where Back leads from the settings and high score screens
+    return_to: Screen,
+
+    // Selection of whichever menu is on screen
+    menu: Menu,
+    mode: Mode,
+
+    // Which keys do what, and the action waiting for a new key on the controls screen
+    bindings: Bindings,
+    rebinding: Option<Action>,
+
+    // The pad, if one is plugged in. With --virtual-gamepad it's fed from the
+    // numeric keypad through `virtual_pad` instead.
+    gamepad: Gamepad,
+    virtual_pad: Option<VirtualGamepad>,
+
+    // Seconds left before the game over menu takes input
+    menu_delay: f32,
+
+    // Set by the Quit menu item; main() ends the loop when it sees it
+    quit: bool,
+
+    // Top-10 table, loaded at start-up and saved whenever it changes
+    high_scores: HighScores,
+
+    // Initials typed so far while a new high score is being entered, None otherwise
+    initials: Option<String>,
+
+    // Table row of the newest entry, highlighted on the title screen
+    new_rank: Option<usize>,
+
+    // Short notice at the bottom of the screen and how many seconds it has left
+    toast: Option<(String, f32)>,
+
+    // Textures are stored here and handed to entities when they draw.
     // This means we only upload each image to the GPU once.
     tex_background: Texture2D,
+    tex_player: Texture2D,
     tex_bullet: Texture2D,
     tex_big: Texture2D,
     tex_medium: Texture2D,
@@ -43,37 +231,87 @@ pub struct Game {
 }
 
 impl Game {
-    // Async because macroquad's texture loading is async (works on both native and WASM).
-    pub async fn new() -> Self {
+    // Async because macroquad's texture and sound loading is async (works on both native and WASM).
+    pub async fn new(options: LaunchOptions) -> Self {
         // Load all textures from the assets/ folder next to the executable.
         let tex_background = load_texture("assets/background.png").await.unwrap();
+        let tex_player     = load_texture("assets/toilet.png").await.unwrap();
         let tex_bullet     = load_texture("assets/bullet.png").await.unwrap();
         let tex_big        = load_texture("assets/poopbig.png").await.unwrap();
         let tex_medium     = load_texture("assets/poopmid.png").await.unwrap();
         let tex_small      = load_texture("assets/poopsmall.png").await.unwrap();
 
         // Linear filtering = smooth scaling. Use Nearest if you want pixel-art crispness.
-        for tex in [&tex_background, &tex_bullet, &tex_big, &tex_medium, &tex_small] {
+        for tex in [&tex_background, &tex_player, &tex_bullet, &tex_big, &tex_medium, &tex_small] {
             tex.set_filter(FilterMode::Linear);
         }
 
-        let player = Player::new().await;
+        // Every sound is synthesized at start-up (see synth.rs); there are no audio files
+        let audio = Audio::load().await;
+        audio.start_music();
+
+        // A replay brings its own seed, playfield size and rules; otherwise start a normal run.
+        let playback = Self::load_replay(options.replay.as_deref());
+        let (seed, bounds, rule_set) = match &playback {
+            Some(p) => (p.replay().seed, p.replay().bounds, p.replay().rule_set),
+            None => (options.seed.unwrap_or_else(Self::random_seed), WORLD_SIZE, Mode::Classic.rule_set()),
+        };
+        let world = World::new(bounds, rule_set.rules(), Self::rng_from_seed(seed));
+        let playback_active = playback.is_some();
 
-        // Spawn initial asteroids avoiding the player's starting position
-        let asteroids = Self::spawn_asteroids(INITIAL_ASTEROIDS, player.pos, &tex_big);
+        let virtual_pad = options.virtual_gamepad.then(VirtualGamepad::new);
+        let gamepad = match &virtual_pad {
+            Some(pad) => Gamepad::new(Box::new(pad.clone())),
+            None => Gamepad::system(),
+        };
 
-        // Initialize the CRT shader + render target.
+        // Initialize the post-processing chain + its render targets.
         // This must happen after the macroquad context is ready (i.e. inside main).
-        let crt = CrtEffect::new();
+        // Only bloom and the CRT pass are on by default; F5-F7 and F9 toggle the others.
+        // Bloom runs before the CRT pass so the glow gets curved and scanlined too.
+        let mut post = PostChain::new(WORLD_SIZE);
+        post.push(PixelatePass::new(), false);
+        post.push(MotionBlurPass::new(), false);
+        post.push(ColorGradePass::new(), false);
+        post.push(BloomPass::new(), true);
+        post.push(CrtPass::new(), true);
 
         Self {
-            player,
-            bullets: vec![],
-            asteroids,
-            state: GameState::Playing,
-            score: 0,
-            crt,
+            world,
+            seed,
+            fixed_seed: options.seed,
+            recording: Replay::new(seed, bounds, rule_set),
+            playback,
+            status: None,
+            life_flash: 0.0,
+            clock: FixedStep::new(SIM_DT),
+            fire_queued: false,
+            hyperspace_queued: false,
+            post,
+            crt_preset: CrtPreset::Arcade,
+            shader_errors: vec![],
+            particles: ParticleSystem::new(),
+            shake: ScreenShake::new(),
+            hit_stop_enabled: true,
+            audio,
+            thrusting: false,
+            // Replays go straight to playback; everything else starts on the title screen
+            screen: if playback_active { Screen::Game } else { Screen::Title },
+            return_to: Screen::Title,
+            menu: Menu::new(),
+            mode: Mode::Classic,
+            bindings: Bindings::load(),
+            rebinding: None,
+            gamepad,
+            virtual_pad,
+            menu_delay: 0.0,
+            quit: false,
+            high_scores: HighScores::load(),
+            initials: None,
+            new_rank: None,
+            toast: None,
             tex_background,
+            tex_player,
             tex_bullet,
             tex_big,
             tex_medium,
@@ -81,198 +319,943 @@ impl Game {
         }
     }
 
-    // Spawns `count` big asteroids at random positions,
-    // retrying each one until it's far enough from `avoid`.
-    fn spawn_asteroids(count: usize, avoid: Vec2, tex: &Texture2D) -> Vec<Asteroid> {
-        (0..count).map(|_| {
-            loop {
-                let pos = vec2(
-                    rand::gen_range(0.0, screen_width()),
-                    rand::gen_range(0.0, screen_height()),
-                );
-                if pos.distance(avoid) > SAFE_RADIUS {
-                    return Asteroid::new(pos, AsteroidSize::Big, tex.clone());
-                }
-                // If too close, loop again and try a new random position
+    // A seed for runs that weren't given one, taken from the clock.
+    fn random_seed() -> u64 {
+        (miniquad::date::now() * 1000.0) as u64
+    }
+
+    // Days since 1970 (UTC): the same for everyone, all day
+    fn daily_seed() -> u64 {
+        (miniquad::date::now() / 86_400.0) as u64
+    }
+
+    // The world's generator. Everything random in a run (spawns, splits)
+    // comes from this one generator, so the seed alone decides the layout.
+    fn rng_from_seed(seed: u64) -> RandGenerator {
+        let rng = RandGenerator::new();
+        rng.srand(seed);
+        rng
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_replay(path: Option<&str>) -> Option<Playback> {
+        let path = path?;
+        match Replay::load(std::path::Path::new(path)) {
+            Ok(replay) => Some(Playback::new(replay)),
+            Err(e) => {
+                eprintln!("Couldn't load replay '{path}': {e}");
+                None
             }
-        }).collect()
+        }
+    }
+
+    // Browser builds can't open files from a path.
+    #[cfg(target_arch = "wasm32")]
+    fn load_replay(_path: Option<&str>) -> Option<Playback> {
+        None
+    }
+
+    // Whether the player switched to another tab or window since the last call
+    // (see the cacaroids plugin in index.html). A native window keeps running when
+    // it loses focus, so there's nothing to check there.
+    #[cfg(target_arch = "wasm32")]
+    fn focus_lost() -> bool {
+        extern "C" {
+            fn cacaroids_focus_lost() -> bool;
+        }
+        unsafe { cacaroids_focus_lost() }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn focus_lost() -> bool {
+        false
     }
 
-    // Called every frame. Handles input, physics, and collision detection.
+    // Writes the run that just ended to replays/ (native) or offers it as a download (WASM).
+    fn save_replay(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let name = format!("replays/{}-{}.cacr", self.seed, miniquad::date::now() as u64);
+            self.status = Some(match self.recording.save(std::path::Path::new(&name)) {
+                Ok(()) => format!("Replay saved to {name}"),
+                Err(e) => format!("Couldn't save replay: {e}"),
+            });
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.recording.download();
+            self.status = Some("Replay downloaded".to_string());
+        }
+    }
+
+    // Reads the keyboard and the gamepad, through the actions, into the world's Input struct.
+    // Fire and hyperspace come from the latches filled in update(), not from the keys directly.
+    fn read_input(&mut self) -> Input {
+        let keys = self.action_down(Action::RotateRight) as i32 - self.action_down(Action::RotateLeft) as i32;
+        let stick = self.gamepad.turn(self.world.player.rotation, TURN_SPEED * SIM_DT);
+        Input {
+            turn: (keys as f32 + stick).clamp(-1.0, 1.0),
+            thrust: self.action_down(Action::Thrust) || self.gamepad.trigger() > gamepad::TRIGGER_THRESHOLD,
+            fire: std::mem::take(&mut self.fire_queued),
+            hyperspace: std::mem::take(&mut self.hyperspace_queued),
+        }
+    }
+
+    // An action's keys or pad buttons are held
+    fn action_down(&self, action: Action) -> bool {
+        self.bindings.down(action) || controls::pad_buttons(action).iter().any(|&b| self.gamepad.down(b))
+    }
+
+    // ...or went down this frame
+    fn action_pressed(&self, action: Action) -> bool {
+        self.bindings.pressed(action) || controls::pad_buttons(action).iter().any(|&b| self.gamepad.pressed(b))
+    }
+
+    // Called every frame. Feeds the real frame time into the fixed-step accumulator
+    // and runs as many simulation steps as fit (zero, one or several).
     pub fn update(&mut self) {
-        // If not playing, only listen for restart input
-        if self.state != GameState::Playing {
-            if is_key_pressed(KeyCode::R) {
-                self.restart();
+        self.post.fit_to_window();
+        self.shader_errors = self.post.reload_shaders();
+        self.life_flash = (self.life_flash - get_frame_time()).max(0.0);
+        if let Some((_, time_left)) = &mut self.toast {
+            *time_left -= get_frame_time();
+            if *time_left <= 0.0 {
+                self.toast = None;
+            }
+        }
+
+        self.menu_delay = (self.menu_delay - get_frame_time()).max(0.0);
+
+        // Pads can come and go at any time
+        if let Some(pad) = &self.virtual_pad {
+            pad.set_from_numpad();
+        }
+        let plugged = self.gamepad.update();
+        if let Some(connected) = plugged {
+            self.show_toast(format!("Gamepad {}", if connected { "connected" } else { "disconnected" }));
+        }
+
+        // C cycles the CRT look, F5-F7 toggle the other passes, any time
+        if self.letter_hotkey(KeyCode::C) {
+            self.cycle_crt_preset();
+        }
+        let toggles = [
+            (KeyCode::F5, "Pixelate"),
+            (KeyCode::F6, "Motion blur"),
+            (KeyCode::F7, "Color grading"),
+            (KeyCode::F9, "Bloom"),
+        ];
+        for (key, pass) in toggles {
+            if is_key_pressed(key) {
+                self.toggle_pass(pass);
             }
+        }
+        if is_key_pressed(KeyCode::F8) {
+            self.rotate_passes();
+        }
+        if is_key_pressed(KeyCode::F10) {
+            self.toggle_afterglow();
+        }
+
+        // Volume: -/= for master, M and N mute/unmute the music and the effects
+        let volumes = self.audio.volumes();
+        if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::Equal) {
+            let step = if is_key_pressed(KeyCode::Minus) { -0.1 } else { 0.1 };
+            self.set_volumes(Volumes { master: volumes.master + step, ..volumes });
+        }
+        if self.letter_hotkey(KeyCode::M) {
+            let music = if volumes.music > 0.0 { 0.0 } else { Volumes::default().music };
+            self.set_volumes(Volumes { music, ..volumes });
+        }
+        if self.letter_hotkey(KeyCode::N) {
+            let sfx = if volumes.sfx > 0.0 { 0.0 } else { Volumes::default().sfx };
+            self.set_volumes(Volumes { sfx, ..volumes });
+        }
+
+        // F3 / F4: hit-stop and screen shake, for anyone who'd rather do without
+        if is_key_pressed(KeyCode::F3) {
+            self.toggle_hit_stop();
+        }
+        if is_key_pressed(KeyCode::F4) {
+            self.toggle_shake();
+        }
+
+        // Particles and shake run on the frame clock, so explosions play out over the
+        // game over screen (and through hit-stops) too. A paused run freezes them as well.
+        let frame_time = get_frame_time().min(MAX_FRAME_TIME);
+        let frozen = self.run_visible() && self.screen != Screen::Game;
+        if !frozen {
+            self.particles.update(frame_time, self.world.bounds);
+            self.shake.update(frame_time);
+        }
+
+        // Loops follow what happened in the last frame's steps
+        let playing = self.screen == Screen::Game && self.world.state == GameState::Playing;
+        self.audio.set_loop(Loop::Thrust, playing && self.thrusting && self.world.player.alive);
+        self.audio.set_loop(Loop::Saucer, playing && !self.world.saucers.is_empty());
+
+        // Switching to another tab, or losing the pad, pauses the run. Focus is asked
+        // about every frame so an old focus change can't pause the next run the moment it starts.
+        if (Self::focus_lost() || plugged == Some(false)) && playing {
+            self.set_screen(Screen::Paused);
             return;
         }
 
-        // --- PLAYER UPDATE ---
-        // player.update() returns Some(pos) if the player fired a bullet this frame
-        if let Some(bullet_pos) = self.player.update() {
-            // Compute the forward direction from the player's current rotation
-            let dir = Vec2::from_angle(self.player.rotation - std::f32::consts::FRAC_PI_2);
-            self.bullets.push(Bullet::new(bullet_pos, dir, self.tex_bullet.clone()));
+        match self.screen {
+            Screen::Game => self.update_run(frame_time),
+            Screen::Paused if self.action_pressed(Action::Pause) => self.set_screen(Screen::Game),
+            Screen::Controls if self.rebinding.is_some() => self.update_rebinding(),
+            _ => {
+                if !self.run_visible() {
+                    self.drift_asteroids(frame_time);
+                }
+                self.update_menu();
+            }
         }
+    }
 
-        // --- BULLET UPDATE ---
-        for b in self.bullets.iter_mut() {
-            b.update();
+    // A run on screen: playing, entering initials, or on the game over menu
+    fn update_run(&mut self, frame_time: f32) {
+        // A new high score: the keyboard belongs to the initials entry until it's done
+        if self.initials.is_some() {
+            self.update_initials();
+            return;
         }
 
-        // --- ASTEROID UPDATE ---
-        for a in self.asteroids.iter_mut() {
-            a.update();
+        // If not playing, the game over menu plus the restart / S shortcuts
+        if self.world.state != GameState::Playing {
+            if self.action_pressed(Action::Restart) {
+                self.start_run();
+                return;
+            }
+            if self.letter_hotkey(KeyCode::S) && self.playback.is_none() {
+                self.save_replay();
+            }
+            if self.menu_delay <= 0.0 {
+                self.update_menu();
+            }
+            return;
         }
 
-        // --- BULLET <-> ASTEROID COLLISIONS ---
-        // We collect new child asteroids separately to avoid mutating
-        // the vec while iterating over it (Rust won't allow that).
-        let mut new_asteroids: Vec<Asteroid> = vec![];
+        if is_key_pressed(KeyCode::Escape) || self.action_pressed(Action::Pause) {
+            self.set_screen(Screen::Paused);
+            return;
+        }
 
-        for b in self.bullets.iter_mut() {
-            if !b.alive { continue; } // skip already-dead bullets
+        if self.action_pressed(Action::Fire) {
+            self.fire_queued = true;
+        }
+        if self.action_pressed(Action::Hyperspace) {
+            self.hyperspace_queued = true;
+        }
 
-            for a in self.asteroids.iter_mut() {
-                if !a.alive { continue; } // skip already-dead asteroids
+        // During a hit-stop the simulation doesn't get any time at all,
+        // and the steps left over from the frame it started in are dropped
+        self.clock.add_frame(frame_time);
+        while self.clock.next_step() {
+            let input = match &mut self.playback {
+                Some(playback) => playback.next_input(),
+                None => {
+                    let input = self.read_input();
+                    self.recording.push(&input)
+                }
+            };
+            self.world.step(&input, SIM_DT);
+            self.handle_events();
 
-                // Simple circle-circle collision check
-                if b.pos.distance(a.pos) < b.radius() + a.radius() {
-                    b.alive = false; // bullet is consumed
-                    a.alive = false; // asteroid is destroyed
-                    self.score += a.size.score();
+            self.thrusting = input.thrust;
+            let player = &self.world.player;
+            if input.thrust && player.alive {
+                self.particles.exhaust(player.pos, player.rotation, player.vel);
+            }
 
-                    // Split into 2 smaller asteroids (or nothing if already Small)
-                    let children = a.split(&self.tex_medium, &self.tex_small);
-                    new_asteroids.extend(children);
+            // The step may have ended the round; freeze the world as it is.
+            if self.world.state != GameState::Playing {
+                self.audio.play(Sfx::GameOver, 1.0);
+                self.audio.stop_loops();
+                self.clock.drop_pending();
+                self.menu.reset();
+                self.menu_delay = GAME_OVER_MENU_DELAY;
+
+                // Replays don't get to enter the table
+                if self.playback.is_none() && self.high_scores.qualifies(self.world.score) {
+                    // Throw away whatever was typed during the run
+                    while get_char_pressed().is_some() {}
+                    self.initials = Some(String::new());
                 }
+                break;
+            }
+        }
+    }
+
+    // Behind the title and its menus, asteroids drift by.
+    // They're moved directly (no World::step), as none of this is recorded.
+    fn drift_asteroids(&mut self, frame_time: f32) {
+        self.clock.add_frame(frame_time);
+        while self.clock.next_step() {
+            for a in self.world.asteroids.iter_mut() {
+                a.update(SIM_DT, self.world.bounds);
             }
         }
+    }
 
-        // Now it's safe to add the new asteroids
-        self.asteroids.extend(new_asteroids);
+    // Whether a run is on screen: being played, paused, or behind a menu opened from either
+    fn run_visible(&self) -> bool {
+        match self.screen {
+            Screen::Game | Screen::Paused => true,
+            Screen::Settings | Screen::Controls | Screen::HighScores => self.return_to != Screen::Title,
+            Screen::Title => false,
+        }
+    }
 
-        // --- PLAYER <-> ASTEROID COLLISIONS ---
-        if self.player.alive {
-            for a in self.asteroids.iter() {
-                if !a.alive { continue; }
-                if self.player.pos.distance(a.pos) < self.player.radius() + a.radius() {
-                    self.player.alive = false;
-                    self.state = GameState::GameOver;
-                    return; // no need to check further
+    fn set_screen(&mut self, screen: Screen) {
+        self.screen = screen;
+        self.menu.reset();
+    }
+
+    // Opens a screen that Back returns from
+    fn open(&mut self, screen: Screen) {
+        self.return_to = self.screen;
+        self.set_screen(screen);
+    }
+
+    // Rows of the menu on the current screen (none while playing or typing initials)
+    fn menu_items(&self) -> Vec<MenuItem<MenuAction>> {
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        let percent = |x: f32| (x * 100.0).round() as i32;
+        match self.screen {
+            Screen::Title => {
+                let mut items = vec![
+                    MenuItem::new("Start", MenuAction::Play),
+                    MenuItem::new(format!("Mode: {}", self.mode.name()), MenuAction::Mode),
+                    MenuItem::new("Settings", MenuAction::Settings),
+                    MenuItem::new("High scores", MenuAction::HighScores),
+                ];
+                // A browser tab can't close itself
+                if !cfg!(target_arch = "wasm32") {
+                    items.push(MenuItem::new("Quit", MenuAction::Quit));
                 }
+                items
             }
+            Screen::HighScores => vec![MenuItem::new("Back", MenuAction::Back)],
+            Screen::Settings => {
+                let v = self.audio.volumes();
+                let bloom = self.post.is_enabled("Bloom");
+                let scanlines = self.post.get::<CrtPass>().map_or(0.0, |crt| crt.settings().scanline_strength);
+                vec![
+                    MenuItem::new(format!("Volume: {}%", percent(v.master)), MenuAction::Volume),
+                    MenuItem::new(format!("Music: {}%", percent(v.music)), MenuAction::Music),
+                    MenuItem::new(format!("Effects: {}%", percent(v.sfx)), MenuAction::Effects),
+                    MenuItem::new(format!("CRT: {}", self.crt_preset.name()), MenuAction::Crt),
+                    MenuItem::new(format!("Scanlines: {}%", percent(scanlines)), MenuAction::Scanlines),
+                    MenuItem::new(format!("Bloom: {}", on_off(bloom)), MenuAction::Bloom),
+                    MenuItem::new(format!("Screen shake: {}", on_off(self.shake.enabled)), MenuAction::Shake),
+                    MenuItem::new(format!("Hit-stop: {}", on_off(self.hit_stop_enabled)), MenuAction::HitStop),
+                    MenuItem::new(format!("Stick: {}", if self.gamepad.point_to_aim { "point to aim" } else { "turn" }), MenuAction::StickAim),
+                    MenuItem::new("Controls", MenuAction::Controls),
+                    MenuItem::new("Back", MenuAction::Back),
+                ]
+            }
+            Screen::Controls => {
+                let mut items: Vec<_> = Action::ALL.iter()
+                    .map(|&action| {
+                        let keys: Vec<String> = self.bindings.keys(action).iter().map(|&k| controls::key_name(k)).collect();
+                        let keys = if keys.is_empty() { "-".to_string() } else { keys.join(", ") };
+                        MenuItem::new(format!("{}: {keys}", action.name()), MenuAction::Bind(action))
+                    })
+                    .collect();
+                items.push(MenuItem::new("Reset to defaults", MenuAction::ResetControls));
+                items.push(MenuItem::new("Back", MenuAction::Back));
+                items
+            }
+            Screen::Paused => vec![
+                MenuItem::new("Resume", MenuAction::Resume),
+                MenuItem::new("Restart", MenuAction::Play),
+                MenuItem::new("Settings", MenuAction::Settings),
+                MenuItem::new("Quit to title", MenuAction::Title),
+            ],
+            Screen::Game => {
+                if self.world.state == GameState::Playing || self.initials.is_some() {
+                    return vec![];
+                }
+                let mut items = vec![MenuItem::new("Play again", MenuAction::Play)];
+                if self.playback.is_none() {
+                    items.push(MenuItem::new("Save replay", MenuAction::SaveReplay));
+                }
+                items.push(MenuItem::new("High scores", MenuAction::HighScores));
+                items.push(MenuItem::new("Title", MenuAction::Title));
+                items
+            }
+        }
+    }
+
+    fn update_menu(&mut self) {
+        let items = self.menu_items();
+        let input = MenuInput::from_keyboard().or(self.gamepad.menu_input());
+        match self.menu.update(&items, input) {
+            Some(MenuEvent::Select(action)) => self.menu_action(action, 0),
+            Some(MenuEvent::Adjust(action, step)) => self.menu_action(action, step),
+            Some(MenuEvent::Back) => self.menu_back(),
+            None => {}
+        }
+    }
+
+    // Carries out a menu row: `step` is 0 for Enter, -1 / +1 for left / right
+    fn menu_action(&mut self, action: MenuAction, step: i32) {
+        // Left / right only change settings; everything else needs Enter
+        let adjustable = matches!(
+            action,
+            MenuAction::Mode | MenuAction::Volume | MenuAction::Music | MenuAction::Effects
+                | MenuAction::Crt | MenuAction::Scanlines | MenuAction::Bloom | MenuAction::Shake | MenuAction::HitStop | MenuAction::StickAim
+        );
+        if step != 0 && !adjustable {
+            return;
+        }
+
+        let volumes = self.audio.volumes();
+        let volume_step = step as f32 * 0.1;
+        match action {
+            MenuAction::Play       => self.start_run(),
+            MenuAction::Mode       => self.mode = self.mode.next(),
+            MenuAction::Settings   => self.open(Screen::Settings),
+            MenuAction::HighScores => self.open(Screen::HighScores),
+            MenuAction::Quit       => self.quit = true,
+            MenuAction::Resume     => self.set_screen(Screen::Game),
+            MenuAction::SaveReplay => self.save_replay(),
+            MenuAction::Title      => {
+                self.audio.stop_loops();
+                self.set_screen(Screen::Title);
+            }
+            // Volumes only move with left / right; the row shows the value, so no toast
+            MenuAction::Volume     => self.audio.set_volumes(Volumes { master: volumes.master + volume_step, ..volumes }),
+            MenuAction::Music      => self.audio.set_volumes(Volumes { music: volumes.music + volume_step, ..volumes }),
+            MenuAction::Effects    => self.audio.set_volumes(Volumes { sfx: volumes.sfx + volume_step, ..volumes }),
+            MenuAction::Crt        => self.cycle_crt_preset(),
+            MenuAction::Scanlines  => self.adjust_scanlines(step),
+            MenuAction::Bloom      => self.toggle_pass("Bloom"),
+            MenuAction::Shake      => self.toggle_shake(),
+            MenuAction::HitStop    => self.toggle_hit_stop(),
+            MenuAction::StickAim   => self.gamepad.point_to_aim = !self.gamepad.point_to_aim,
+            // Not open(): Back from here goes to Settings, and Settings keeps its own way back
+            MenuAction::Controls   => self.set_screen(Screen::Controls),
+            MenuAction::Bind(action) => self.rebinding = Some(action),
+            MenuAction::ResetControls => {
+                self.bindings = Bindings::default();
+                self.save_bindings();
+            }
+            MenuAction::Back       => self.menu_back(),
+        }
+    }
+
+    // Escape, or the Back row
+    fn menu_back(&mut self) {
+        match self.screen {
+            Screen::Settings | Screen::HighScores => self.set_screen(self.return_to),
+            Screen::Controls => self.set_screen(Screen::Settings),
+            Screen::Paused => self.set_screen(Screen::Game),
+            Screen::Title | Screen::Game => {}
         }
+    }
 
-        // --- CLEANUP ---
-        // Remove entities that were marked dead this frame.
-        // retain() keeps only elements where the closure returns true.
-        self.bullets.retain(|b| b.alive);
-        self.asteroids.retain(|a| a.alive);
+    // Controls screen, waiting for a key: the next bindable key press goes to the
+    // action, Escape cancels. Other keys (F keys, Enter, ...) are ignored.
+    fn update_rebinding(&mut self) {
+        let Some(action) = self.rebinding else { return };
+        if is_key_pressed(KeyCode::Escape) {
+            self.rebinding = None;
+            return;
+        }
+        let Some(key) = get_keys_pressed().into_iter().find(|&k| controls::is_bindable(k)) else { return };
+        self.bindings.bind(action, key);
+        self.rebinding = None;
+        self.save_bindings();
+    }
 
-        // --- VICTORY CHECK ---
-        // Player cleared all asteroids including all split children
-        if self.asteroids.is_empty() {
-            self.state = GameState::Victory;
+    fn save_bindings(&mut self) {
+        if let Err(e) = self.bindings.save() {
+            self.show_toast(format!("Couldn't save controls: {e}"));
+        }
+    }
+
+    // A letter shortcut (C, M, N, S) was pressed. They're off while typing initials or
+    // picking a key, and give way to keys that are bound to an action.
+    fn letter_hotkey(&self, key: KeyCode) -> bool {
+        is_key_pressed(key) && self.initials.is_none() && self.rebinding.is_none() && !self.bindings.is_bound(key)
+    }
+
+    // Starts a new run in the selected mode
+    fn start_run(&mut self) {
+        self.restart();
+        self.set_screen(Screen::Game);
+    }
+
+    // Three letters, Backspace to correct, Enter to confirm
+    // On a pad: up / down change the last letter, South adds another (or confirms
+    // the third one), East deletes.
+    fn update_initials(&mut self) {
+        let pad = if self.menu_delay > 0.0 { MenuInput::default() } else { self.gamepad.menu_input() };
+        let Some(initials) = &mut self.initials else { return };
+        while let Some(c) = get_char_pressed() {
+            if c.is_ascii_alphabetic() && initials.len() < 3 {
+                initials.push(c.to_ascii_uppercase());
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) || pad.back {
+            initials.pop();
+        }
+        if pad.up || pad.down {
+            let letter = match initials.pop() {
+                Some(c) => {
+                    let step = if pad.up { 1 } else { 25 };
+                    (b'A' + (c as u8 - b'A' + step) % 26) as char
+                }
+                None => 'A',
+            };
+            initials.push(letter);
+        }
+        let mut confirm = is_key_pressed(KeyCode::Enter);
+        if pad.confirm {
+            if initials.len() < 3 {
+                initials.push('A');
+            } else {
+                confirm = true;
+            }
+        }
+        if confirm && initials.len() == 3 {
+            let entry = HighScore {
+                initials: std::mem::take(initials),
+                score: self.world.score,
+                wave: self.world.wave,
+                seed: self.seed,
+                date: highscores::today(),
+            };
+            self.initials = None;
+            self.new_rank = self.high_scores.insert(entry);
+            self.status = Some(match self.high_scores.save() {
+                Ok(()) => "High score saved".to_string(),
+                Err(e) => format!("Couldn't save high scores: {e}"),
+            });
+        }
+    }
+
+    fn cycle_crt_preset(&mut self) {
+        self.crt_preset = self.crt_preset.next();
+        if let Some(crt) = self.post.get_mut::<CrtPass>() {
+            crt.set_preset(self.crt_preset);
+        }
+        self.show_toast(format!("CRT: {}", self.crt_preset.name()));
+    }
+
+    // Fine-tunes the current CRT look's scanlines, for screens where the preset's are
+    // too heavy (or too light). Picking a preset again resets them.
+    fn adjust_scanlines(&mut self, step: i32) {
+        if let Some(crt) = self.post.get_mut::<CrtPass>() {
+            let mut settings = crt.settings();
+            settings.scanline_strength = (settings.scanline_strength + step as f32 * SCANLINE_STEP).clamp(0.0, MAX_SCANLINES);
+            crt.set_settings(settings);
+        }
+    }
+
+    fn toggle_hit_stop(&mut self) {
+        self.hit_stop_enabled = !self.hit_stop_enabled;
+        self.clock.thaw();
+        self.show_toast(format!("Hit-stop: {}", if self.hit_stop_enabled { "on" } else { "off" }));
+    }
+
+    fn toggle_shake(&mut self) {
+        self.shake.enabled = !self.shake.enabled;
+        self.show_toast(format!("Screen shake: {}", if self.shake.enabled { "on" } else { "off" }));
+    }
+
+    // Switches a post-processing pass on or off.
+    fn toggle_pass(&mut self, name: &str) {
+        let enabled = !self.post.is_enabled(name);
+        self.post.set_enabled(name, enabled);
+        self.show_toast(format!("{name}: {}", if enabled { "on" } else { "off" }));
+    }
+
+    // Phosphor afterglow is a mode of the bloom pass rather than a pass of its own.
+    fn toggle_afterglow(&mut self) {
+        let Some(bloom) = self.post.get_mut::<BloomPass>() else { return };
+        bloom.afterglow = !bloom.afterglow;
+        let enabled = bloom.afterglow;
+        self.show_toast(format!("Afterglow: {}", if enabled { "on" } else { "off" }));
+    }
+
+    // Moves the last pass to the front of the chain, so each press tries a new order
+    // (e.g. CRT before pixelation gives chunky scanlines).
+    fn rotate_passes(&mut self) {
+        let count = self.post.passes().count();
+        if count > 1 {
+            self.post.move_pass(count - 1, 0);
+        }
+        let order: Vec<&str> = self.post.passes().map(|(name, _)| name).collect();
+        self.show_toast(format!("Order: {}", order.join(" > ")));
+    }
+
+    fn set_volumes(&mut self, volumes: Volumes) {
+        self.audio.set_volumes(volumes);
+        let v = self.audio.volumes();
+        let percent = |x: f32| (x * 100.0).round() as i32;
+        self.show_toast(format!(
+            "Volume {}%  Music {}%  Effects {}%",
+            percent(v.master), percent(v.music), percent(v.sfx),
+        ));
+    }
+
+    fn show_toast(&mut self, text: String) {
+        self.toast = Some((text, TOAST_TIME));
+    }
+
+    // Reacts to what happened in the last simulation step.
+    fn handle_events(&mut self) {
+        let mut stop: f32 = 0.0;
+        for event in self.world.events.drain(..) {
+            match event {
+                WorldEvent::ExtraLife => {
+                    self.life_flash = EXTRA_LIFE_FLASH;
+                    self.audio.play(Sfx::ExtraLife, 1.0);
+                }
+                // Saucer shots are quieter, so the player's own stand out
+                WorldEvent::Fired { faction } => {
+                    let volume = if faction == Faction::Player { 0.6 } else { 0.3 };
+                    self.audio.play(Sfx::Fire, volume);
+                }
+                WorldEvent::AsteroidDestroyed { pos, size } => {
+                    self.particles.debris(pos, size);
+                    self.audio.play(match size {
+                        AsteroidSize::Big    => Sfx::ExplosionBig,
+                        AsteroidSize::Medium => Sfx::ExplosionMedium,
+                        AsteroidSize::Small  => Sfx::ExplosionSmall,
+                    }, 1.0);
+                    let (trauma, freeze) = match size {
+                        AsteroidSize::Big    => (0.35, 0.05),
+                        AsteroidSize::Medium => (0.2, 0.0),
+                        AsteroidSize::Small  => (0.1, 0.0),
+                    };
+                    self.shake.add_trauma(trauma);
+                    stop = stop.max(freeze);
+                }
+                WorldEvent::SaucerDestroyed { pos } => {
+                    self.particles.saucer_explosion(pos);
+                    self.audio.play(Sfx::ExplosionMedium, 1.0);
+                    self.shake.add_trauma(0.3);
+                    stop = stop.max(0.05);
+                }
+                WorldEvent::PlayerDied { pos } => {
+                    self.particles.ship_explosion(pos);
+                    self.audio.play(Sfx::ExplosionBig, 1.0);
+                    self.shake.add_trauma(0.8);
+                    stop = stop.max(0.2);
+                }
+            }
+        }
+
+        // Several hits in one step don't stack; the longest freeze wins.
+        // The rest of the frame's steps are dropped, not saved up.
+        if self.hit_stop_enabled {
+            self.clock.hit_stop(stop);
+        }
+    }
+
+    fn asteroid_texture(&self, size: AsteroidSize) -> &Texture2D {
+        match size {
+            AsteroidSize::Big    => &self.tex_big,
+            AsteroidSize::Medium => &self.tex_medium,
+            AsteroidSize::Small  => &self.tex_small,
         }
     }
 
     // Called every frame after update(). Pure rendering, no logic here.
     pub fn draw(&self) {
-        // --- CRT BEGIN ---
+        // How far we are between the last simulation step and the next one.
+        // Entities draw themselves blended between their previous and current state.
+        let alpha = self.clock.alpha();
+
+        // --- POST-PROCESSING BEGIN ---
         // Redirect all draw calls to the off-screen render target.
-        // Everything drawn between begin() and end() gets the CRT shader applied.
-        self.crt.begin();
+        // Everything drawn between begin() and end() goes through the pass chain.
+        self.post.begin(self.shake.offset());
 
         // --- BACKGROUND ---
-        // Stretch the background texture to fill the entire window
+        // Stretch the background texture to fill the entire world
         draw_texture_ex(
             &self.tex_background,
             0.0, 0.0,
             WHITE,
             DrawTextureParams {
-                dest_size: Some(vec2(screen_width(), screen_height())),
+                dest_size: Some(WORLD_SIZE),
                 ..Default::default()
             },
         );
 
         // --- ASTEROIDS ---
-        for a in self.asteroids.iter().filter(|a| a.alive) {
-            a.draw();
+        for a in self.world.asteroids.iter().filter(|a| a.alive) {
+            a.draw(self.asteroid_texture(a.size), alpha, self.world.bounds);
         }
 
-        // --- BULLETS ---
-        for b in self.bullets.iter().filter(|b| b.alive) {
-            b.draw();
+        // --- PARTICLES ---
+        // Drawn over the asteroids, so debris flies in front of the remaining chunks
+        self.particles.draw(self.world.bounds);
+
+        // The run (if there is one) on top of the playfield, then whatever screen is up
+        if self.run_visible() {
+            self.draw_run(alpha);
+        }
+        match self.screen {
+            Screen::Title      => self.draw_title(),
+            Screen::HighScores => self.draw_high_scores(),
+            Screen::Settings   => self.draw_overlay("SETTINGS", "Left / Right to change, Esc to go back"),
+            Screen::Controls   => match self.rebinding {
+                Some(action) => self.draw_overlay("CONTROLS", &format!("Press a key for {} (Esc to cancel)", action.name())),
+                None => self.draw_overlay("CONTROLS", "Enter to add a key, Esc to go back"),
+            },
+            Screen::Paused     => self.draw_overlay("PAUSED", "Esc or P to resume"),
+            Screen::Game       => self.draw_run_overlay(),
         }
 
-        // --- PLAYER ---
-        if self.player.alive {
-            self.player.draw();
+        // --- TOAST ---
+        if let Some((text, _)) = &self.toast {
+            let tw = measure_text(text, None, 28, 1.0).width;
+            draw_text(text, WORLD_SIZE.x / 2.0 - tw / 2.0, WORLD_SIZE.y - 30.0, 28.0, WHITE);
         }
 
-        // --- HUD: SCORE ---
-        // Draw a semi-transparent dark background behind the score text
-        // so it's readable over any background color.
-        let score_text = format!("SCORE: {}", self.score);
-        let text_size = measure_text(&score_text, None, 32, 1.0);
+        // --- POST-PROCESSING END ---
+        // Run the passes and flush the result to the real screen.
+        self.post.end();
+
+        // --- SHADER ERRORS ---
+        // Drawn straight onto the window, after post-processing, so a broken
+        // shader can't make its own error message unreadable.
+        let mut y = 24.0;
+        for line in self.shader_errors.iter().flat_map(|error| error.lines()) {
+            draw_text(line, 10.0, y, 20.0, RED);
+            y += 20.0;
+        }
+    }
+
+    // Draws a HUD text panel at the top of the screen, `x` being where the text starts.
+    // The semi-transparent dark background keeps it readable over any background color.
+    // Returns the right edge of the panel so the next one can be placed after it.
+    fn draw_hud_panel(text: &str, x: f32) -> f32 {
+        let text_size = measure_text(text, None, 32, 1.0);
         let pad_x = 12.0;
         let pad_y = 8.0;
-        let rx = 15.0; // text x position
-        let ry = 15.0; // text y position (top of box)
-        let bx = rx - pad_x;
-        let by = ry - pad_y;
+        let y = 15.0; // text y position (top of box)
         let bw = text_size.width + pad_x * 2.0;
         let bh = text_size.height + pad_y * 2.0;
 
         // Background panel first, then text on top
-        draw_rectangle(bx, by, bw, bh, Color::new(0.0, 0.0, 0.0, 0.6));
-        draw_text(&score_text, rx, ry + text_size.height, 32.0, WHITE);
+        draw_rectangle(x - pad_x, y - pad_y, bw, bh, Color::new(0.0, 0.0, 0.0, 0.6));
+        draw_text(text, x, y + text_size.height, 32.0, WHITE);
+
+        x - pad_x + bw
+    }
+
+    // Same look as draw_hud_panel(), but shows one small ship icon per remaining life.
+    fn draw_lives_panel(&self, x: f32) -> f32 {
+        let icon = 24.0;
+        let gap = 4.0;
+        let pad_x = 12.0;
+        let pad_y = 8.0;
+        let y = 15.0;
+        let count = self.world.lives as f32;
+        let bw = (count * icon + (count - 1.0).max(0.0) * gap).max(icon) + pad_x * 2.0;
+        let bh = icon + pad_y * 2.0;
 
-        // --- OVERLAYS (Game Over / Victory) ---
-        match self.state {
-            GameState::GameOver => self.draw_overlay("GAME OVER", "Press R to restart"),
-            GameState::Victory  => self.draw_overlay("YOU WIN!", "Press R to play again"),
-            GameState::Playing  => {}
+        // Pulse the panel gold for a moment after an extra life
+        let panel = if self.life_flash > 0.0 && (self.life_flash * 8.0) as i32 % 2 == 0 {
+            Color::new(0.9, 0.7, 0.1, 0.8)
+        } else {
+            Color::new(0.0, 0.0, 0.0, 0.6)
+        };
+        draw_rectangle(x - pad_x, y - pad_y, bw, bh, panel);
+        for i in 0..self.world.lives {
+            draw_texture_ex(
+                &self.tex_player,
+                x + i as f32 * (icon + gap),
+                y - 1.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(icon, icon)),
+                    ..Default::default()
+                },
+            );
         }
 
-        // --- CRT END ---
-        // Flush the render target to the real screen with the CRT shader applied.
-        self.crt.end();
+        x - pad_x + bw
     }
 
-    // Draws a centered fullscreen dim overlay with a title and subtitle.
-    // Used for Game Over and Victory screens.
+    // Draws a centered fullscreen dim overlay with a title, a subtitle and the current
+    // screen's menu. Over a run it also shows the run's seed and the status message.
+    // Used for game over, pause and settings.
     fn draw_overlay(&self, title: &str, subtitle: &str) {
-        let cx = screen_width() / 2.0;
-        let cy = screen_height() / 2.0;
+        let cx = WORLD_SIZE.x / 2.0;
+        let items = self.menu_items();
+        let show_seed = self.run_visible();
 
-        // Semi-transparent black overlay over the whole screen
-        draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.6));
+        // Semi-transparent black overlay over the whole world
+        draw_rectangle(0.0, 0.0, WORLD_SIZE.x, WORLD_SIZE.y, Color::new(0.0, 0.0, 0.0, 0.6));
+
+        // The whole block (title, subtitle, seed + status, menu) is centered vertically
+        let info_height = if show_seed { 70.0 } else { 0.0 };
+        let menu_height = if items.is_empty() { 0.0 } else { 70.0 + Menu::height(items.len()) };
+        let mut y = (WORLD_SIZE.y - (100.0 + info_height + menu_height)) / 2.0 + 50.0;
 
         // Center the title text horizontally
         let tw = measure_text(title, None, 64, 1.0).width;
-        draw_text(title, cx - tw / 2.0, cy - 20.0, 64.0, WHITE);
+        draw_text(title, cx - tw / 2.0, y, 64.0, WHITE);
 
         // Center the subtitle text horizontally
+        y += 50.0;
         let sw = measure_text(subtitle, None, 32, 1.0).width;
-        draw_text(subtitle, cx - sw / 2.0, cy + 30.0, 32.0, LIGHTGRAY);
+        draw_text(subtitle, cx - sw / 2.0, y, 32.0, LIGHTGRAY);
+
+        // Seed in small print, so a run can be reported or replayed
+        if show_seed {
+            let seed_text = format!("SEED: {}", self.seed);
+            let dw = measure_text(&seed_text, None, 24, 1.0).width;
+            draw_text(&seed_text, cx - dw / 2.0, y + 40.0, 24.0, GRAY);
+
+            if let Some(status) = &self.status {
+                let mw = measure_text(status, None, 24, 1.0).width;
+                draw_text(status, cx - mw / 2.0, y + 70.0, 24.0, GRAY);
+            }
+            y += info_height;
+        }
+
+        self.menu.draw(&items, cx, y + 70.0);
     }
 
-    // Resets all game state back to initial conditions without reloading textures.
-    // Textures are just cloned (ref-counted pointer copy) so this is fast.
-    fn restart(&mut self) {
-        self.bullets.clear();
-        self.score = 0;
-        self.asteroids = Self::spawn_asteroids(INITIAL_ASTEROIDS, vec2(640.0, 360.0), &self.tex_big);
+    // Initials entry or the game over menu, once the run has ended
+    fn draw_run_overlay(&self) {
+        if let Some(initials) = &self.initials {
+            let typed: String = (0..3).map(|i| initials.chars().nth(i).unwrap_or('_')).collect();
+            let how = if self.gamepad.is_connected() { "Up / Down to pick letters" } else { "Enter to confirm" };
+            self.draw_overlay("NEW HIGH SCORE!", &format!("Type your initials: {typed}   ({how})"));
+        } else if self.world.state == GameState::GameOver {
+            let subtitle = if self.playback.is_some() { "End of replay" } else { "R to restart, S to save the replay" };
+            self.draw_overlay("GAME OVER", subtitle);
+        }
+    }
 
-        // Reset player state manually — avoids reloading the texture from disk
-        self.player.pos = vec2(640.0, 360.0);
-        self.player.vel = Vec2::ZERO;
-        self.player.rotation = 0.0;
-        self.player.alive = true;
+    // Everything but the asteroids: saucers, bullets, the player and the HUD.
+    // None of it is shown on the title screen.
+    fn draw_run(&self, alpha: f32) {
+        // --- SAUCERS ---
+        for s in self.world.saucers.iter().filter(|s| s.alive) {
+            s.draw(alpha, self.world.bounds);
+        }
+
+        // --- BULLETS ---
+        for b in self.world.bullets.iter().filter(|b| b.alive) {
+            b.draw(&self.tex_bullet, alpha, self.world.bounds);
+        }
+
+        // --- PLAYER ---
+        if self.world.player.alive {
+            self.world.player.draw(&self.tex_player, alpha, self.world.bounds);
+        }
+
+        // --- HUD: SCORE + LIVES + WAVE ---
+        let score_right = Self::draw_hud_panel(&format!("SCORE: {}", self.world.score), 15.0);
+        let lives_right = self.draw_lives_panel(score_right + 30.0);
+        Self::draw_hud_panel(&format!("WAVE: {}", self.world.wave), lives_right + 30.0);
 
-        self.state = GameState::Playing;
+        // --- HUD: REPLAY TAG ---
+        if self.playback.is_some() {
+            let tag = "REPLAY";
+            let size = measure_text(tag, None, 32, 1.0);
+            draw_text(tag, WORLD_SIZE.x - size.width - 15.0, 15.0 + size.height, 32.0, RED);
+        }
+
+        // --- WAVE BANNER ---
+        // Announce the upcoming wave during the intermission
+        if self.world.intermission > 0.0 {
+            let banner = format!("WAVE {}", self.world.wave + 1);
+            let bw = measure_text(&banner, None, 64, 1.0).width;
+            draw_text(&banner, WORLD_SIZE.x / 2.0 - bw / 2.0, WORLD_SIZE.y / 2.0, 64.0, WHITE);
+        }
+    }
+
+    // Title and the main menu
+    fn draw_title(&self) {
+        let cx = WORLD_SIZE.x / 2.0;
+        draw_rectangle(0.0, 0.0, WORLD_SIZE.x, WORLD_SIZE.y, Color::new(0.0, 0.0, 0.0, 0.4));
+
+        let title = "CACAROIDS";
+        let tw = measure_text(title, None, 96, 1.0).width;
+        draw_text(title, cx - tw / 2.0, 200.0, 96.0, WHITE);
+
+        self.menu.draw(&self.menu_items(), cx, 330.0);
     }
-}
\ No newline at end of file
+
+    // The high score table, newest entry highlighted
+    fn draw_high_scores(&self) {
+        let cx = WORLD_SIZE.x / 2.0;
+        draw_rectangle(0.0, 0.0, WORLD_SIZE.x, WORLD_SIZE.y, Color::new(0.0, 0.0, 0.0, 0.6));
+
+        let title = "HIGH SCORES";
+        let tw = measure_text(title, None, 64, 1.0).width;
+        draw_text(title, cx - tw / 2.0, 110.0, 64.0, WHITE);
+
+        // Columns: right edge for numbers, left edge for text. The font isn't
+        // monospaced, so each cell is placed on its own.
+        let x = cx - 300.0;
+        let columns = [(x + 40.0, true), (x + 80.0, false), (x + 290.0, true), (x + 390.0, true), (x + 440.0, false)];
+        let draw_row = |cells: [&str; 5], y: f32, color: Color| {
+            for (text, (cell_x, right)) in cells.iter().zip(columns) {
+                let offset = if right { measure_text(text, None, 28, 1.0).width } else { 0.0 };
+                draw_text(text, cell_x - offset, y, 28.0, color);
+            }
+        };
+        draw_row(["#", "NAME", "SCORE", "WAVE", "DATE"], 180.0, GRAY);
+
+        if self.high_scores.entries().is_empty() {
+            let empty = "No high scores yet";
+            let ew = measure_text(empty, None, 28, 1.0).width;
+            draw_text(empty, cx - ew / 2.0, 230.0, 28.0, LIGHTGRAY);
+        }
+        for (i, entry) in self.high_scores.entries().iter().enumerate() {
+            let rank = (i + 1).to_string();
+            let score = entry.score.to_string();
+            let wave = entry.wave.to_string();
+            let color = if self.new_rank == Some(i) { GOLD } else { WHITE };
+            draw_row([&rank, &entry.initials, &score, &wave, &entry.date], 220.0 + i as f32 * 34.0, color);
+        }
+
+        self.menu.draw(&self.menu_items(), cx, 620.0);
+    }
+
+    // Set once the player picks Quit
+    pub fn quit_requested(&self) -> bool {
+        self.quit
+    }
+
+    // Starts a brand new world. Textures stay loaded on the Game, so this is fast.
+    // Daily runs use the day's seed and the daily rules. Otherwise it reuses the launch seed if there is
+    // one, or rolls a new seed. Always hands control back to the player, even after
+    // watching a replay.
+    fn restart(&mut self) {
+        self.seed = match self.mode {
+            Mode::Daily => Self::daily_seed(),
+            Mode::Classic => self.fixed_seed.unwrap_or_else(Self::random_seed),
+        };
+        let rule_set = self.mode.rule_set();
+        self.world = World::new(WORLD_SIZE, rule_set.rules(), Self::rng_from_seed(self.seed));
+        self.recording = Replay::new(self.seed, WORLD_SIZE, rule_set);
+        self.playback = None;
+        self.status = None;
+        self.life_flash = 0.0;
+        self.clock.reset();
+        self.fire_queued = false;
+        self.hyperspace_queued = false;
+        self.particles.clear();
+        self.shake.reset();
+        self.thrusting = false;
+        self.audio.stop_loops();
+        self.initials = None;
+        self.new_rank = None;
+    }
+}
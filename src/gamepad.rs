@@ -0,0 +1,458 @@
+use macroquad::prelude::*;
+use std::cell::Cell;
+use std::rc::Rc;
+use crate::menu::MenuInput;
+
+// Gamepad support.
+//
+// A `GamepadSource` reports one pad as a plain GamepadState, once per frame:
+//   - natively that's gilrs,
+//   - in the browser the cacaroids plugin in index.html reads navigator.getGamepads(),
+//   - a VirtualGamepad is a pad that only exists in code, for trying the pad paths
+//     (dead-zones, point-to-aim, menus) without one plugged in.
+// Gamepad sits on top of a source and adds dead-zones, button presses and hot-plug
+// notices. Which button does what lives with the keyboard bindings (controls.rs).
+
+// Sticks report less than this (0..1) as centered. Worn sticks rarely rest at exactly 0.
+const STICK_DEAD_ZONE: f32 = 0.2;
+
+// How far a trigger has to be pulled to count as held
+pub const TRIGGER_THRESHOLD: f32 = 0.3;
+
+// How far the stick has to be pushed to move through a menu
+const MENU_STICK_THRESHOLD: f32 = 0.6;
+
+// Buttons, by position (South is A on an Xbox pad, Cross on a PlayStation one)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Button {
+    South,
+    East,
+    West,
+    North,
+    LeftShoulder,
+    RightShoulder,
+    Select,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl Button {
+    pub const ALL: [Button; 12] = [
+        Button::South,
+        Button::East,
+        Button::West,
+        Button::North,
+        Button::LeftShoulder,
+        Button::RightShoulder,
+        Button::Select,
+        Button::Start,
+        Button::DPadUp,
+        Button::DPadDown,
+        Button::DPadLeft,
+        Button::DPadRight,
+    ];
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&b| b == self).unwrap()
+    }
+}
+
+// Everything we read off a pad, raw (no dead-zones yet)
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct GamepadState {
+    // -1..1 on each axis, y pointing down like the screen
+    pub left_stick: Vec2,
+    // 0..1
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+    // Indexed by Button::index
+    pub buttons: [bool; Button::ALL.len()],
+}
+
+impl GamepadState {
+    pub fn is_down(&self, button: Button) -> bool {
+        self.buttons[button.index()]
+    }
+
+    pub fn set_button(&mut self, button: Button, down: bool) {
+        self.buttons[button.index()] = down;
+    }
+}
+
+pub trait GamepadSource {
+    // The pad's state right now, or None if no pad is connected
+    fn poll(&mut self) -> Option<GamepadState>;
+}
+
+// A pad that only exists in code. Clones share the same state, so keep one
+// to set() and hand another to Gamepad::new.
+#[derive(Clone, Default)]
+pub struct VirtualGamepad {
+    state: Rc<Cell<Option<GamepadState>>>,
+}
+
+impl VirtualGamepad {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // None = unplugged
+    pub fn set(&self, state: Option<GamepadState>) {
+        self.state.set(state);
+    }
+
+    // Drives the pad from the numeric keypad, so the pad code can be tried
+    // without one (--virtual-gamepad). 1-9 around 5 are the stick, 0 is South,
+    // the decimal point East, + the right trigger, * Start and / Select.
+    pub fn set_from_numpad(&self) {
+        let axis = |minus: [KeyCode; 3], plus: [KeyCode; 3]| {
+            let held = |keys: [KeyCode; 3]| keys.iter().any(|&k| is_key_down(k));
+            held(plus) as i32 as f32 - held(minus) as i32 as f32
+        };
+        let x = axis([KeyCode::Kp4, KeyCode::Kp7, KeyCode::Kp1], [KeyCode::Kp6, KeyCode::Kp9, KeyCode::Kp3]);
+        let y = axis([KeyCode::Kp8, KeyCode::Kp7, KeyCode::Kp9], [KeyCode::Kp2, KeyCode::Kp1, KeyCode::Kp3]);
+
+        let mut state = GamepadState {
+            left_stick: vec2(x, y).normalize_or_zero(),
+            right_trigger: if is_key_down(KeyCode::KpAdd) { 1.0 } else { 0.0 },
+            ..Default::default()
+        };
+        state.set_button(Button::South, is_key_down(KeyCode::Kp0));
+        state.set_button(Button::East, is_key_down(KeyCode::KpDecimal));
+        state.set_button(Button::Start, is_key_down(KeyCode::KpMultiply));
+        state.set_button(Button::Select, is_key_down(KeyCode::KpDivide));
+        self.set(Some(state));
+    }
+}
+
+impl GamepadSource for VirtualGamepad {
+    fn poll(&mut self) -> Option<GamepadState> {
+        self.state.get()
+    }
+}
+
+// Native pads, through gilrs. The pad that was used last is the one that counts.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct GilrsSource {
+    // None if gilrs couldn't start (no udev, ...); then there are simply no pads
+    gilrs: Option<gilrs::Gilrs>,
+    active: Option<gilrs::GamepadId>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl GilrsSource {
+    pub fn new() -> Self {
+        let gilrs = match gilrs::Gilrs::new() {
+            Ok(gilrs) | Err(gilrs::Error::NotImplemented(gilrs)) => Some(gilrs),
+            Err(e) => {
+                eprintln!("Gamepads unavailable: {e}");
+                None
+            }
+        };
+        Self { gilrs, active: None }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl GamepadSource for GilrsSource {
+    fn poll(&mut self) -> Option<GamepadState> {
+        let gilrs = self.gilrs.as_mut()?;
+
+        // gilrs only updates its state while its events are drained
+        while let Some(event) = gilrs.next_event() {
+            if matches!(event.event, gilrs::EventType::ButtonPressed(..)) {
+                self.active = Some(event.id);
+            }
+        }
+
+        let id = self.active
+            .filter(|&id| gilrs.connected_gamepad(id).is_some())
+            .or_else(|| gilrs.gamepads().next().map(|(id, _)| id))?;
+        self.active = Some(id);
+        let pad = gilrs.gamepad(id);
+
+        let trigger = |button| pad.button_data(button).map_or(0.0, |data| data.value());
+        let mut state = GamepadState {
+            // gilrs has y pointing up
+            left_stick: vec2(pad.value(gilrs::Axis::LeftStickX), -pad.value(gilrs::Axis::LeftStickY)),
+            left_trigger: trigger(gilrs::Button::LeftTrigger2),
+            right_trigger: trigger(gilrs::Button::RightTrigger2),
+            ..Default::default()
+        };
+        let buttons = [
+            (Button::South, gilrs::Button::South),
+            (Button::East, gilrs::Button::East),
+            (Button::West, gilrs::Button::West),
+            (Button::North, gilrs::Button::North),
+            (Button::LeftShoulder, gilrs::Button::LeftTrigger),
+            (Button::RightShoulder, gilrs::Button::RightTrigger),
+            (Button::Select, gilrs::Button::Select),
+            (Button::Start, gilrs::Button::Start),
+            (Button::DPadUp, gilrs::Button::DPadUp),
+            (Button::DPadDown, gilrs::Button::DPadDown),
+            (Button::DPadLeft, gilrs::Button::DPadLeft),
+            (Button::DPadRight, gilrs::Button::DPadRight),
+        ];
+        for (ours, theirs) in buttons {
+            state.set_button(ours, pad.is_pressed(theirs));
+        }
+        Some(state)
+    }
+}
+
+// Browser pads, through the Gamepad API (see the cacaroids plugin in index.html).
+// Browsers only report a pad once a button on it has been pressed.
+#[cfg(target_arch = "wasm32")]
+pub struct BrowserSource;
+
+#[cfg(target_arch = "wasm32")]
+impl GamepadSource for BrowserSource {
+    fn poll(&mut self) -> Option<GamepadState> {
+        extern "C" {
+            // Fills `out` with stick x, stick y, left trigger, right trigger, then
+            // one 0/1 per Button in Button::ALL order. False if there's no pad.
+            fn cacaroids_gamepad(out: *mut f32) -> bool;
+        }
+        let mut raw = [0.0_f32; 4 + Button::ALL.len()];
+        if !unsafe { cacaroids_gamepad(raw.as_mut_ptr()) } {
+            return None;
+        }
+        let mut state = GamepadState {
+            left_stick: vec2(raw[0], raw[1]),
+            left_trigger: raw[2],
+            right_trigger: raw[3],
+            ..Default::default()
+        };
+        for (i, button) in Button::ALL.into_iter().enumerate() {
+            state.set_button(button, raw[4 + i] > 0.5);
+        }
+        Some(state)
+    }
+}
+
+pub struct Gamepad {
+    source: Box<dyn GamepadSource>,
+    state: Option<GamepadState>,
+    prev: Option<GamepadState>,
+
+    // Stick mode: off = left / right turns the ship like the keys do,
+    // on = the ship turns to face wherever the stick points
+    pub point_to_aim: bool,
+}
+
+impl Gamepad {
+    pub fn new(source: Box<dyn GamepadSource>) -> Self {
+        Self { source, state: None, prev: None, point_to_aim: false }
+    }
+
+    // Whatever pads this platform has
+    pub fn system() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let source = GilrsSource::new();
+        #[cfg(target_arch = "wasm32")]
+        let source = BrowserSource;
+        Self::new(Box::new(source))
+    }
+
+    // Reads the pad. Once per frame, before any of the queries below.
+    // Returns Some(connected) when a pad was just plugged in or pulled out.
+    pub fn update(&mut self) -> Option<bool> {
+        self.prev = self.state;
+        self.state = self.source.poll();
+        let connected = self.state.is_some();
+        (connected != self.prev.is_some()).then_some(connected)
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.state.is_some()
+    }
+
+    pub fn down(&self, button: Button) -> bool {
+        self.state.is_some_and(|s| s.is_down(button))
+    }
+
+    // Went down this frame
+    pub fn pressed(&self, button: Button) -> bool {
+        self.down(button) && !self.prev.is_some_and(|s| s.is_down(button))
+    }
+
+    // The further pulled of the two triggers. Either one thrusts, so the ship
+    // can be flown with whichever hand isn't on the stick's side.
+    pub fn trigger(&self) -> f32 {
+        self.state.map_or(0.0, |s| s.left_trigger.max(s.right_trigger))
+    }
+
+    // Left stick with the dead-zone taken out: zero inside it, and rescaled so
+    // it still reaches full deflection at the edge. Radial, so diagonals aren't
+    // cut off the way a per-axis dead-zone would.
+    pub fn stick(&self) -> Vec2 {
+        let raw = self.state.map_or(Vec2::ZERO, |s| s.left_stick);
+        let length = raw.length().min(1.0);
+        if length < STICK_DEAD_ZONE {
+            return Vec2::ZERO;
+        }
+        raw.normalize() * (length - STICK_DEAD_ZONE) / (1.0 - STICK_DEAD_ZONE)
+    }
+
+    // How hard to turn (-1..1) for a ship currently facing `rotation`
+    // (radians, 0 = up), turning at most `max_step` radians per step.
+    pub fn turn(&self, rotation: f32, max_step: f32) -> f32 {
+        let stick = self.stick();
+        if !self.point_to_aim {
+            return stick.x;
+        }
+        if stick == Vec2::ZERO {
+            return 0.0;
+        }
+        // Shortest way round to where the stick points, at full speed until the
+        // last step, which turns just far enough to land on it
+        let target = stick.to_angle() + std::f32::consts::FRAC_PI_2;
+        let diff = (target - rotation + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI;
+        (diff / max_step).clamp(-1.0, 1.0)
+    }
+
+    // D-pad or stick to move, South to pick, East to go back
+    pub fn menu_input(&self) -> MenuInput {
+        let stick = |s: Option<GamepadState>| s.map_or(Vec2::ZERO, |s| s.left_stick);
+        let (now, before) = (stick(self.state), stick(self.prev));
+        // A stick "press" is the moment it crosses the threshold
+        let pushed = |now: f32, before: f32| now > MENU_STICK_THRESHOLD && before <= MENU_STICK_THRESHOLD;
+        MenuInput {
+            up: self.pressed(Button::DPadUp) || pushed(-now.y, -before.y),
+            down: self.pressed(Button::DPadDown) || pushed(now.y, before.y),
+            left: self.pressed(Button::DPadLeft) || pushed(-now.x, -before.x),
+            right: self.pressed(Button::DPadRight) || pushed(now.x, before.x),
+            confirm: self.pressed(Button::South),
+            back: self.pressed(Button::East),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    fn pad() -> (VirtualGamepad, Gamepad) {
+        let pad = VirtualGamepad::new();
+        let gamepad = Gamepad::new(Box::new(pad.clone()));
+        (pad, gamepad)
+    }
+
+    fn stick(x: f32, y: f32) -> Option<GamepadState> {
+        Some(GamepadState { left_stick: vec2(x, y), ..Default::default() })
+    }
+
+    #[test]
+    fn radial_dead_zone() {
+        let (pad, mut gamepad) = pad();
+
+        pad.set(stick(0.1, 0.0));
+        gamepad.update();
+        assert_eq!(gamepad.stick(), Vec2::ZERO);
+
+        pad.set(stick(0.0, -1.0));
+        gamepad.update();
+        assert!((gamepad.stick().length() - 1.0).abs() < 1e-5);
+
+        // Full deflection on a diagonal isn't cut off either
+        pad.set(stick(0.8, 0.8));
+        gamepad.update();
+        assert!((gamepad.stick().length() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn point_to_aim_takes_the_short_way_round() {
+        let (pad, mut gamepad) = pad();
+        gamepad.point_to_aim = true;
+
+        // Facing up, stick right / left
+        pad.set(stick(1.0, 0.0));
+        gamepad.update();
+        assert_eq!(gamepad.turn(0.0, 0.05), 1.0);
+        pad.set(stick(-1.0, 0.0));
+        gamepad.update();
+        assert_eq!(gamepad.turn(0.0, 0.05), -1.0);
+
+        // Facing just short of straight down, target just past it: turn on
+        // through PI rather than all the way back
+        let target = PI + 0.1;
+        let dir = Vec2::from_angle(target - FRAC_PI_2);
+        pad.set(stick(dir.x, dir.y));
+        gamepad.update();
+        assert_eq!(gamepad.turn(PI - 0.1, 0.05), 1.0);
+    }
+
+    #[test]
+    fn point_to_aim_lands_on_the_target() {
+        let (pad, mut gamepad) = pad();
+        gamepad.point_to_aim = true;
+        pad.set(stick(1.0, 0.0));
+        gamepad.update();
+
+        let max_step = 0.07;
+        let mut rotation = 0.0;
+        let mut turns = vec![];
+        for _ in 0..100 {
+            let turn = gamepad.turn(rotation, max_step);
+            if turn == 0.0 {
+                break;
+            }
+            turns.push(turn);
+            rotation += turn * max_step;
+        }
+
+        let (last, rest) = turns.split_last().unwrap();
+        assert!(rest.iter().all(|&t| t == 1.0));
+        assert!(*last > 0.0 && *last < 1.0);
+        assert!((rotation - FRAC_PI_2).abs() < 1e-5);
+    }
+
+    #[test]
+    fn pressed_only_on_the_frame_it_goes_down() {
+        let (pad, mut gamepad) = pad();
+        let mut state = GamepadState::default();
+        pad.set(Some(state));
+        gamepad.update();
+        assert!(!gamepad.pressed(Button::South));
+
+        state.set_button(Button::South, true);
+        pad.set(Some(state));
+        gamepad.update();
+        assert!(gamepad.pressed(Button::South));
+
+        gamepad.update();
+        assert!(gamepad.down(Button::South));
+        assert!(!gamepad.pressed(Button::South));
+    }
+
+    #[test]
+    fn either_trigger_counts() {
+        let (pad, mut gamepad) = pad();
+        pad.set(Some(GamepadState { left_trigger: 0.7, right_trigger: 0.1, ..Default::default() }));
+        gamepad.update();
+        assert_eq!(gamepad.trigger(), 0.7);
+
+        pad.set(Some(GamepadState { left_trigger: 0.0, right_trigger: 0.4, ..Default::default() }));
+        gamepad.update();
+        assert_eq!(gamepad.trigger(), 0.4);
+    }
+
+    #[test]
+    fn update_reports_hot_plug() {
+        let (pad, mut gamepad) = pad();
+        assert_eq!(gamepad.update(), None);
+
+        pad.set(Some(GamepadState::default()));
+        assert_eq!(gamepad.update(), Some(true));
+        assert_eq!(gamepad.update(), None);
+        assert!(gamepad.is_connected());
+
+        pad.set(None);
+        assert_eq!(gamepad.update(), Some(false));
+        assert!(!gamepad.is_connected());
+    }
+}
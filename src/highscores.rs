@@ -0,0 +1,98 @@
+use crate::storage;
+
+// The top-10 table.
+//
+// Stored as plain text, one entry per line, best first:
+//     ABC 12340 7 1700000000123 2026-10-16
+// (initials, score, wave reached, seed, date). The seed is there so a claimed
+// score can be checked by replaying the run with the same seed.
+
+pub const MAX_ENTRIES: usize = 10;
+
+const STORAGE_KEY: &str = "highscores";
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct HighScore {
+    pub initials: String,
+    pub score: u32,
+    pub wave: u32,
+    pub seed: u64,
+    // YYYY-MM-DD, in UTC
+    pub date: String,
+}
+
+pub struct HighScores {
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    // Missing or unreadable storage just means an empty table. Lines that
+    // don't parse are skipped rather than throwing the whole table away.
+    pub fn load() -> Self {
+        let mut entries: Vec<HighScore> = storage::load(STORAGE_KEY)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(Self::parse_line)
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.score));
+        entries.truncate(MAX_ENTRIES);
+        Self { entries }
+    }
+
+    fn parse_line(line: &str) -> Option<HighScore> {
+        let mut fields = line.split_whitespace();
+        let entry = HighScore {
+            initials: fields.next()?.to_string(),
+            score: fields.next()?.parse().ok()?,
+            wave: fields.next()?.parse().ok()?,
+            seed: fields.next()?.parse().ok()?,
+            date: fields.next()?.to_string(),
+        };
+        Some(entry)
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let text: String = self.entries.iter()
+            .map(|e| format!("{} {} {} {} {}\n", e.initials, e.score, e.wave, e.seed, e.date))
+            .collect();
+        storage::save(STORAGE_KEY, &text)
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    // Whether `score` would make it onto the table
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0 && (self.entries.len() < MAX_ENTRIES || self.entries.iter().any(|e| score > e.score))
+    }
+
+    // Adds an entry in score order and returns its position (0 = top),
+    // or None if it didn't make the cut. Ties go below the older entries.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+        let rank = self.entries.iter().position(|e| entry.score > e.score).unwrap_or(self.entries.len());
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+}
+
+// Today's date as YYYY-MM-DD (UTC). Works in the browser too, unlike SystemTime.
+pub fn today() -> String {
+    let days = (miniquad::date::now() / 86_400.0).floor() as i64;
+
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{year:04}-{month:02}-{day:02}")
+}
@@ -0,0 +1,69 @@
+// Options the game can be started with.
+//
+// Native builds read them from the command line:
+//     cargo run -- --seed 12345
+//     cargo run -- --replay replays/12345-1700000000.cacr
+//     cargo run --release -- --bench-broadphase
+//     cargo run -- --virtual-gamepad
+//
+// The WASM build reads them from the page URL instead:
+//     index.html?seed=12345
+// (the tiny JS plugin that exposes the query string lives in index.html)
+
+#[derive(Default)]
+pub struct LaunchOptions {
+    // Fixed RNG seed for every run. None = pick a fresh random seed each run.
+    pub seed: Option<u64>,
+
+    // Replay file to watch instead of playing (native only).
+    pub replay: Option<String>,
+
+    // Run the collision benchmark and exit instead of opening the game (native only).
+    pub bench_broadphase: bool,
+
+    // Play with a pretend gamepad on the numeric keypad instead of real pads (native only).
+    pub virtual_gamepad: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn parse() -> LaunchOptions {
+    let mut options = LaunchOptions::default();
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => match args.next().map(|v| v.parse::<u64>()) {
+                Some(Ok(seed)) => options.seed = Some(seed),
+                _ => eprintln!("--seed expects a whole number, ignoring it"),
+            },
+            "--replay" => match args.next() {
+                Some(path) => options.replay = Some(path),
+                None => eprintln!("--replay expects a file path, ignoring it"),
+            },
+            "--bench-broadphase" => options.bench_broadphase = true,
+            "--virtual-gamepad" => options.virtual_gamepad = true,
+            other => eprintln!("Unknown argument '{other}', ignoring it"),
+        }
+    }
+
+    options
+}
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    // Provided by the cacaroids plugin in index.html.
+    // Returns the ?seed= query value, or a negative number if it's missing or invalid.
+    fn cacaroids_url_seed() -> f64;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn parse() -> LaunchOptions {
+    // JS numbers are doubles, so URL seeds are limited to 2^53 — plenty for sharing.
+    let seed = unsafe { cacaroids_url_seed() };
+    LaunchOptions {
+        seed: (seed >= 0.0).then_some(seed as u64),
+        replay: None,
+        bench_broadphase: false,
+        virtual_gamepad: false,
+    }
+}
@@ -1,12 +1,35 @@
 use macroquad::prelude::*;
 
+mod audio;
 mod game;
+mod gamepad;
+mod highscores;
+mod launch;
+mod menu;
+mod particles;
 mod player;
+mod postfx;
+mod replay;
+mod rules;
+mod saucer;
 mod asteroid;
+mod bloom;
 mod bullet;
+mod controls;
 mod shader;
+mod shake;
+mod spatial;
+mod storage;
+mod synth;
+mod timestep;
+mod torus;
+mod world;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod bench;
 
 use game::Game;
+use launch::LaunchOptions;
 
 fn window_conf() -> Conf {
     Conf {
@@ -18,12 +41,28 @@ fn window_conf() -> Conf {
     }
 }
 
-#[macroquad::main(window_conf)]
-async fn main() {
-    let mut game = Game::new().await;
+// Plain main instead of #[macroquad::main] so headless modes can run
+// (and exit) before a window is ever opened.
+fn main() {
+    let options = launch::parse();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if options.bench_broadphase {
+        bench::broadphase();
+        return;
+    }
+
+    macroquad::Window::from_config(window_conf(), run(options));
+}
+
+async fn run(options: LaunchOptions) {
+    let mut game = Game::new(options).await;
     loop {
         game.update();
+        if game.quit_requested() {
+            break;
+        }
         game.draw();
         next_frame().await;
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,138 @@
+use macroquad::prelude::*;
+
+// A vertical list of choices, used by every menu screen (title, pause, settings,
+// game over, ...).
+//
+// The menu only keeps track of which row is selected. The items themselves are
+// rebuilt by the caller whenever it needs them, so labels like "Volume: 80%"
+// are always current. `A` is whatever the caller wants back when a row is picked.
+
+pub struct MenuItem<A> {
+    pub label: String,
+    pub action: A,
+}
+
+impl<A> MenuItem<A> {
+    pub fn new(label: impl Into<String>, action: A) -> Self {
+        Self { label: label.into(), action }
+    }
+}
+
+// One frame's worth of menu navigation, from whatever device is being used
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct MenuInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub confirm: bool,
+    pub back: bool,
+}
+
+impl MenuInput {
+    // Arrow keys to move and change values, Enter to pick, Escape to go back.
+    // Letters are left alone so hotkeys like R and S keep working on menu screens,
+    // and so is Space: someone mashing fire as the run ends shouldn't pick anything.
+    pub fn from_keyboard() -> Self {
+        Self {
+            up: is_key_pressed(KeyCode::Up),
+            down: is_key_pressed(KeyCode::Down),
+            left: is_key_pressed(KeyCode::Left),
+            right: is_key_pressed(KeyCode::Right),
+            confirm: is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter),
+            back: is_key_pressed(KeyCode::Escape),
+        }
+    }
+
+    // Either one: the keyboard and a gamepad can both drive a menu
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            up: self.up || other.up,
+            down: self.down || other.down,
+            left: self.left || other.left,
+            right: self.right || other.right,
+            confirm: self.confirm || other.confirm,
+            back: self.back || other.back,
+        }
+    }
+}
+
+// What the player did with the menu this frame
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MenuEvent<A> {
+    // Enter on a row
+    Select(A),
+    // Left (-1) or right (+1) on a row, for settings with a value
+    Adjust(A, i32),
+    Back,
+}
+
+// Text size and row spacing, in logical units
+const FONT_SIZE: u16 = 36;
+const ROW_HEIGHT: f32 = 44.0;
+
+pub struct Menu {
+    selected: usize,
+}
+
+impl Menu {
+    pub fn new() -> Self {
+        Self { selected: 0 }
+    }
+
+    // Back to the first row, e.g. when a different menu is opened
+    pub fn reset(&mut self) {
+        self.selected = 0;
+    }
+
+    // Moves the selection (wrapping at either end) and reports a pick, if any.
+    pub fn update<A: Copy>(&mut self, items: &[MenuItem<A>], input: MenuInput) -> Option<MenuEvent<A>> {
+        if input.back {
+            return Some(MenuEvent::Back);
+        }
+        if items.is_empty() {
+            return None;
+        }
+        self.selected = self.selected.min(items.len() - 1);
+        if input.up {
+            self.selected = (self.selected + items.len() - 1) % items.len();
+        }
+        if input.down {
+            self.selected = (self.selected + 1) % items.len();
+        }
+
+        let action = items[self.selected].action;
+        if input.confirm {
+            Some(MenuEvent::Select(action))
+        } else if input.left {
+            Some(MenuEvent::Adjust(action, -1))
+        } else if input.right {
+            Some(MenuEvent::Adjust(action, 1))
+        } else {
+            None
+        }
+    }
+
+    // Draws the rows centered on `center_x`, the first one's baseline at `top`.
+    // Returns the y just below the last row.
+    pub fn draw<A>(&self, items: &[MenuItem<A>], center_x: f32, top: f32) -> f32 {
+        let selected = self.selected.min(items.len().saturating_sub(1));
+        let mut y = top;
+        for (i, item) in items.iter().enumerate() {
+            let (text, color) = if i == selected {
+                (format!("> {} <", item.label), GOLD)
+            } else {
+                (item.label.clone(), LIGHTGRAY)
+            };
+            let w = measure_text(&text, None, FONT_SIZE, 1.0).width;
+            draw_text(&text, center_x - w / 2.0, y, FONT_SIZE as f32, color);
+            y += ROW_HEIGHT;
+        }
+        y
+    }
+
+    // Height taken up by `count` rows, for centering a menu on screen
+    pub fn height(count: usize) -> f32 {
+        count as f32 * ROW_HEIGHT
+    }
+}
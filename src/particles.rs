@@ -0,0 +1,151 @@
+use macroquad::prelude::*;
+use macroquad::rand::RandGenerator;
+use crate::asteroid::AsteroidSize;
+use crate::torus;
+
+// Purely cosmetic particles: asteroid debris, ship exhaust and explosions.
+//
+// They live in the Game, not the World, and have their own RNG, so they never
+// touch the simulation and replays stay deterministic.
+//
+// All particles live in one Vec allocated up front. Dead ones are swap-removed,
+// and spawning past MAX_PARTICLES is simply dropped, so nothing allocates per frame.
+
+const MAX_PARTICLES: usize = 8192;
+
+struct Particle {
+    pos: Vec2,
+    vel: Vec2,
+    // Seconds left, and how many it started with (for fading and shrinking)
+    life: f32,
+    max_life: f32,
+    size: f32,
+    color: Color,
+}
+
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    rng: RandGenerator,
+}
+
+// Colours picked at random for each kind of particle
+const DEBRIS_COLORS: [Color; 3] = [
+    Color::new(0.45, 0.28, 0.12, 1.0),
+    Color::new(0.55, 0.35, 0.15, 1.0),
+    Color::new(0.35, 0.22, 0.10, 1.0),
+];
+const EXHAUST_COLORS: [Color; 3] = [
+    Color::new(1.0, 0.85, 0.4, 1.0),
+    Color::new(1.0, 0.55, 0.15, 1.0),
+    Color::new(0.9, 0.3, 0.1, 1.0),
+];
+// Sparks plus white porcelain shards: the ship is a toilet, after all
+const SHIP_COLORS: [Color; 3] = [
+    Color::new(1.0, 1.0, 1.0, 1.0),
+    Color::new(1.0, 0.8, 0.3, 1.0),
+    Color::new(1.0, 0.45, 0.1, 1.0),
+];
+const SAUCER_COLORS: [Color; 3] = [
+    Color::new(0.6, 0.9, 1.0, 1.0),
+    Color::new(0.8, 0.85, 0.9, 1.0),
+    Color::new(1.0, 1.0, 1.0, 1.0),
+];
+
+// Velocity kept per second; the rest bleeds off so bursts slow down and hang
+const DRAG: f32 = 0.3;
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        let rng = RandGenerator::new();
+        rng.srand(0);
+        Self {
+            particles: Vec::with_capacity(MAX_PARTICLES),
+            rng,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    fn spawn(&mut self, pos: Vec2, vel: Vec2, life: f32, size: f32, color: Color) {
+        if self.particles.len() < MAX_PARTICLES {
+            self.particles.push(Particle { pos, vel, life, max_life: life, size, color });
+        }
+    }
+
+    // `count` particles flying out of `pos` in all directions
+    fn burst(&mut self, pos: Vec2, count: usize, speed: (f32, f32), life: (f32, f32), size: (f32, f32), colors: &[Color]) {
+        for _ in 0..count {
+            let dir = Vec2::from_angle(self.rng.gen_range(0.0_f32, std::f32::consts::TAU));
+            let vel = dir * self.rng.gen_range(speed.0, speed.1);
+            let color = colors[self.rng.gen_range(0, colors.len())];
+            let life = self.rng.gen_range(life.0, life.1);
+            let size = self.rng.gen_range(size.0, size.1);
+            self.spawn(pos, vel, life, size, color);
+        }
+    }
+
+    // Brown chunks when an asteroid is hit. Bigger asteroids throw more and bigger bits.
+    pub fn debris(&mut self, pos: Vec2, size: AsteroidSize) {
+        let (count, chunk) = match size {
+            AsteroidSize::Big    => (28, (3.0, 7.0)),
+            AsteroidSize::Medium => (16, (2.5, 5.0)),
+            AsteroidSize::Small  => (8, (2.0, 4.0)),
+        };
+        self.burst(pos, count, (30.0, 160.0), (0.5, 1.2), chunk, &DEBRIS_COLORS);
+    }
+
+    // A few hot particles out of the back of the ship. Called every simulation
+    // step the ship thrusts, so the plume thickens with the step rate, not the frame rate.
+    pub fn exhaust(&mut self, pos: Vec2, rotation: f32, ship_vel: Vec2) {
+        let back = -Vec2::from_angle(rotation - std::f32::consts::FRAC_PI_2);
+        let nozzle = pos + back * 24.0;
+        for _ in 0..2 {
+            let spread = Vec2::from_angle(back.to_angle() + self.rng.gen_range(-0.35, 0.35));
+            let vel = ship_vel + spread * self.rng.gen_range(120.0, 240.0);
+            let color = EXHAUST_COLORS[self.rng.gen_range(0, EXHAUST_COLORS.len())];
+            let life = self.rng.gen_range(0.15, 0.35);
+            let size = self.rng.gen_range(2.0, 4.0);
+            self.spawn(nozzle, vel, life, size, color);
+        }
+    }
+
+    // The ship going up: a big, fast burst of sparks and shards.
+    pub fn ship_explosion(&mut self, pos: Vec2) {
+        self.burst(pos, 160, (60.0, 360.0), (0.6, 1.8), (2.0, 6.0), &SHIP_COLORS);
+    }
+
+    pub fn saucer_explosion(&mut self, pos: Vec2) {
+        self.burst(pos, 60, (40.0, 240.0), (0.4, 1.2), (2.0, 5.0), &SAUCER_COLORS);
+    }
+
+    // Advances every particle by `dt` seconds, wrapping around a playfield of size `bounds`.
+    pub fn update(&mut self, dt: f32, bounds: Vec2) {
+        let drag = DRAG.powf(dt);
+        let mut i = 0;
+        while i < self.particles.len() {
+            let p = &mut self.particles[i];
+            p.life -= dt;
+            if p.life <= 0.0 {
+                self.particles.swap_remove(i);
+                continue;
+            }
+            p.pos = torus::wrap(p.pos + p.vel * dt, bounds);
+            p.vel *= drag;
+            i += 1;
+        }
+    }
+
+    // Squares that shrink and fade out over their life
+    pub fn draw(&self, bounds: Vec2) {
+        for p in self.particles.iter() {
+            let t = p.life / p.max_life;
+            let size = p.size * (0.4 + 0.6 * t);
+            let color = Color { a: p.color.a * t, ..p.color };
+            torus::for_each_ghost(p.pos, size / 2.0, bounds, |pos| {
+                draw_rectangle(pos.x - size / 2.0, pos.y - size / 2.0, size, size, color);
+            });
+        }
+    }
+}
@@ -1,41 +1,79 @@
 use macroquad::prelude::*;
+use crate::torus;
+use crate::world::Input;
+
+// Turning speed at full stick / key, in radians per second
+pub const TURN_SPEED: f32 = 3.0;
 
 pub struct Player {
     pub pos: Vec2,
     pub vel: Vec2,
     pub rotation: f32,       // radians
-    pub texture: Texture2D,
     pub alive: bool,
     shoot_cooldown: f32,
+
+    // Seconds of invulnerability left after a respawn. Zero = can be hit.
+    pub invulnerable: f32,
+
+    // Seconds until hyperspace can be used again
+    pub hyperspace_cooldown: f32,
+
+    // State at the end of the previous simulation step.
+    // draw() blends between this and the current state so motion stays smooth
+    // even when the render rate doesn't line up with the simulation rate.
+    pub prev_pos: Vec2,
+    pub prev_rotation: f32,
 }
 
 impl Player {
-    pub async fn new() -> Self {
-        let texture = load_texture("assets/toilet.png").await.unwrap();
-        texture.set_filter(FilterMode::Linear);
+    pub fn new(pos: Vec2) -> Self {
         Self {
-            pos: vec2(640.0, 360.0),
+            pos,
             vel: Vec2::ZERO,
             rotation: 0.0,
-            texture,
             alive: true,
             shoot_cooldown: 0.0,
+            invulnerable: 0.0,
+            hyperspace_cooldown: 0.0,
+            prev_pos: pos,
+            prev_rotation: 0.0,
         }
     }
 
-    pub fn update(&mut self) -> Option<Vec2> {
-        let dt = get_frame_time();
+    // Brings the player back at `pos`, stationary, facing up,
+    // and protected for `invulnerable` seconds.
+    pub fn respawn(&mut self, pos: Vec2, invulnerable: f32) {
+        *self = Self::new(pos);
+        self.invulnerable = invulnerable;
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.0
+    }
+
+    // Instantly moves the ship to `pos` and kills its momentum.
+    // prev_pos moves too, so the jump isn't drawn as a streak across the screen.
+    pub fn teleport(&mut self, pos: Vec2, cooldown: f32) {
+        self.pos = pos;
+        self.prev_pos = pos;
+        self.vel = Vec2::ZERO;
+        self.hyperspace_cooldown = cooldown;
+    }
+
+    // Advances the player by one fixed simulation step of `dt` seconds,
+    // wrapping around a playfield of size `bounds`.
+    // Returns Some(pos) where a bullet should spawn if the player fired this step.
+    pub fn update(&mut self, input: &Input, dt: f32, bounds: Vec2) -> Option<Vec2> {
+        self.prev_pos = self.pos;
+        self.prev_rotation = self.rotation;
+        self.invulnerable = (self.invulnerable - dt).max(0.0);
+        self.hyperspace_cooldown = (self.hyperspace_cooldown - dt).max(0.0);
 
         // Rotation
-        if is_key_down(KeyCode::Left) || is_key_down(KeyCode::A) {
-            self.rotation -= 3.0 * dt;
-        }
-        if is_key_down(KeyCode::Right) || is_key_down(KeyCode::D) {
-            self.rotation += 3.0 * dt;
-        }
+        self.rotation += input.turn.clamp(-1.0, 1.0) * TURN_SPEED * dt;
 
         // Thrust
-        if is_key_down(KeyCode::Up) || is_key_down(KeyCode::W) {
+        if input.thrust {
             let dir = Vec2::from_angle(self.rotation - std::f32::consts::FRAC_PI_2);
             self.vel += dir * 400.0 * dt;
         }
@@ -50,18 +88,16 @@ impl Player {
 
         self.pos += self.vel * dt;
 
-        // Screen wrap
-        let (w, h) = (screen_width(), screen_height());
-        if self.pos.x < 0.0 { self.pos.x = w; }
-        if self.pos.x > w   { self.pos.x = 0.0; }
-        if self.pos.y < 0.0 { self.pos.y = h; }
-        if self.pos.y > h   { self.pos.y = 0.0; }
+        // Screen wrap, once the centre crosses an edge.
+        // prev_pos is shifted by the same amount so interpolation doesn't
+        // smear the sprite across the whole screen on the wrapping step.
+        let before = self.pos;
+        self.pos = torus::wrap(self.pos, bounds);
+        self.prev_pos += self.pos - before;
 
         // Shooting
         self.shoot_cooldown -= dt;
-        if (is_key_pressed(KeyCode::Space) || is_key_pressed(KeyCode::Z))
-            && self.shoot_cooldown <= 0.0
-        {
+        if input.fire && self.shoot_cooldown <= 0.0 {
             self.shoot_cooldown = 0.25;
             let dir = Vec2::from_angle(self.rotation - std::f32::consts::FRAC_PI_2);
             return Some(self.pos + dir * 32.0);
@@ -70,21 +106,32 @@ impl Player {
         None
     }
 
-    pub fn draw(&self) {
+    // `alpha` is how far we are between the previous and current simulation step (0..1).
+    // Near an edge the ship is also drawn on the opposite side, so it slides across.
+    pub fn draw(&self, texture: &Texture2D, alpha: f32, bounds: Vec2) {
+        // Blink while invulnerable: hidden for every other tenth of a second
+        if self.is_invulnerable() && (self.invulnerable * 10.0) as i32 % 2 == 1 {
+            return;
+        }
+
         let size = 64.0;
-        draw_texture_ex(
-            &self.texture,
-            self.pos.x - size / 2.0,
-            self.pos.y - size / 2.0,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(vec2(size, size)),
-                rotation: self.rotation,
-                pivot: Some(self.pos),
-                ..Default::default()
-            },
-        );
+        let pos = self.prev_pos.lerp(self.pos, alpha);
+        let rotation = self.prev_rotation + (self.rotation - self.prev_rotation) * alpha;
+        torus::for_each_ghost(pos, size / 2.0, bounds, |pos| {
+            draw_texture_ex(
+                texture,
+                pos.x - size / 2.0,
+                pos.y - size / 2.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(size, size)),
+                    rotation,
+                    pivot: Some(pos),
+                    ..Default::default()
+                },
+            );
+        });
     }
 
     pub fn radius(&self) -> f32 { 24.0 }
-}
\ No newline at end of file
+}
@@ -0,0 +1,473 @@
+use std::any::Any;
+use macroquad::prelude::*;
+
+// Post-processing chain.
+//
+// The game draws into an off-screen render target (between begin() and end()).
+// end() then runs each enabled pass in order, ping-ponging between two render
+// targets: pass 1 reads A and writes B, pass 2 reads B and writes A, and so on.
+// Whatever comes out of the last pass is drawn into the letterboxed viewport.
+//
+// Passes can be enabled, disabled, reordered and tuned independently.
+// The CRT effect (shader.rs) and bloom (bloom.rs) are just two of them.
+
+// Shared vertex shader for every full-screen pass: passes UVs through untouched.
+const PASS_VERTEX: &str = include_str!("../assets/shaders/pass.vert");
+
+// One step of the chain.
+pub trait PostPass: Any {
+    fn name(&self) -> &'static str;
+
+    // Reads `input` and draws the processed image into `output`, both `size` pixels.
+    fn apply(&self, input: &Texture2D, output: &RenderTarget, size: Vec2);
+
+    // Called when the chain's targets change size, for passes that keep
+    // render targets of their own. Most passes don't.
+    fn resize(&mut self, _size: Vec2) {}
+
+    // The pass's shaders, so the chain can hot-reload them.
+    fn shaders_mut(&mut self) -> Vec<&mut PassShader>;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+// Camera that maps pixel coordinates 0..size onto `target`.
+// Same orientation as the scene camera, so images stay upright pass after pass.
+pub fn target_camera(target: &RenderTarget, size: Vec2) -> Camera2D {
+    Camera2D {
+        zoom: vec2(2.0 / size.x, 2.0 / size.y),
+        target: size / 2.0,
+        render_target: Some(target.clone()),
+        ..Default::default()
+    }
+}
+
+// Draws `texture` over the whole of `target` through `material` (or the default one).
+pub fn blit(texture: &Texture2D, target: &RenderTarget, size: Vec2, material: Option<&Material>) {
+    set_camera(&target_camera(target, size));
+    if let Some(material) = material {
+        gl_use_material(material);
+    }
+    draw_texture_ex(texture, 0.0, 0.0, WHITE, DrawTextureParams {
+        dest_size: Some(size),
+        ..Default::default()
+    });
+    gl_use_default_material();
+}
+
+pub fn create_target(size: Vec2) -> RenderTarget {
+    let target = render_target(size.x.max(1.0) as u32, size.y.max(1.0) as u32);
+    target.texture.set_filter(FilterMode::Linear);
+    target
+}
+
+// Shaders are only read from disk in native debug builds
+const HOT_RELOAD: bool = cfg!(all(debug_assertions, not(target_arch = "wasm32")));
+
+// Where the shader sources live on disk, relative to the working directory
+// (same as the textures). Every source is also embedded in the binary with include_str!.
+const SHADER_DIR: &str = "assets/shaders";
+
+// How often to check the files for changes
+const SHADER_POLL_INTERVAL: f64 = 0.5;
+
+// The material of a full-screen pass, built from a fragment shader in assets/shaders/.
+//
+// Release and WASM builds only ever use the embedded source. Native debug builds load
+// the file from disk instead and recompile whenever it changes, so shaders can be tweaked
+// while the game runs. If the file doesn't compile, the embedded source is used and the
+// compile error is kept in `error` for the HUD until the file is fixed.
+pub struct PassShader {
+    file: &'static str,
+    material: Material,
+    error: Option<String>,
+
+    // Everything needed to recompile
+    embedded: &'static str,
+    uniforms: &'static [&'static str],
+    textures: &'static [&'static str],
+
+    // File modification time at the last (re)load, and when we last checked it
+    modified: Option<std::time::SystemTime>,
+    last_poll: f64,
+}
+
+impl PassShader {
+    // All uniforms are plain floats. `file` is the name inside assets/shaders/,
+    // `embedded` the include_str! of that same file.
+    pub fn new(
+        file: &'static str,
+        embedded: &'static str,
+        uniforms: &'static [&'static str],
+        textures: &'static [&'static str],
+    ) -> Self {
+        let material = Self::compile(embedded, uniforms, textures).unwrap();
+        let mut shader = Self {
+            file,
+            material,
+            error: None,
+            embedded,
+            uniforms,
+            textures,
+            modified: None,
+            last_poll: get_time(),
+        };
+        if HOT_RELOAD {
+            shader.reload_from_disk();
+        }
+        shader
+    }
+
+    fn compile(fragment: &str, uniforms: &[&str], textures: &[&str]) -> Result<Material, macroquad::Error> {
+        load_material(
+            ShaderSource::Glsl { vertex: PASS_VERTEX, fragment },
+            MaterialParams {
+                uniforms: uniforms.iter().map(|&name| UniformDesc::new(name, UniformType::Float1)).collect(),
+                textures: textures.iter().map(|&name| name.to_string()).collect(),
+                ..Default::default()
+            },
+        )
+    }
+
+    pub fn material(&self) -> &Material {
+        &self.material
+    }
+
+    // Compile error of the file on disk, if it's currently broken
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn file(&self) -> &'static str {
+        self.file
+    }
+
+    // Recompiles if the file changed since the last load. Does nothing in
+    // release and WASM builds.
+    pub fn reload_if_changed(&mut self) {
+        let now = get_time();
+        if !HOT_RELOAD || now - self.last_poll < SHADER_POLL_INTERVAL {
+            return;
+        }
+        self.last_poll = now;
+
+        let path = format!("{SHADER_DIR}/{}", self.file);
+        let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        if modified != self.modified {
+            self.reload_from_disk();
+        }
+    }
+
+    fn reload_from_disk(&mut self) {
+        let path = format!("{SHADER_DIR}/{}", self.file);
+        self.modified = std::fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+
+        // No file (e.g. running from another directory): quietly stick with the embedded one
+        let Ok(source) = std::fs::read_to_string(&path) else {
+            return;
+        };
+        match Self::compile(&source, self.uniforms, self.textures) {
+            Ok(material) => {
+                self.material = material;
+                self.error = None;
+            }
+            Err(err) => {
+                self.material = Self::compile(self.embedded, self.uniforms, self.textures).unwrap();
+                self.error = Some(err.to_string());
+            }
+        }
+    }
+}
+
+struct Slot {
+    pass: Box<dyn PostPass>,
+    enabled: bool,
+}
+
+pub struct PostChain {
+    slots: Vec<Slot>,
+
+    // The two ping-pong targets. The scene is drawn into targets[0].
+    targets: [RenderTarget; 2],
+
+    // Size of the game world in logical units. Everything between begin() and end()
+    // is drawn in these coordinates, whatever the window size.
+    logical_size: Vec2,
+
+    // Where the game shows up in the window: the largest rect with the world's
+    // aspect ratio that fits, centred. The rest is black bars (letterbox/pillarbox).
+    viewport: Rect,
+}
+
+impl PostChain {
+    pub fn new(logical_size: Vec2) -> Self {
+        // The targets are sized to the on-screen viewport, so they stay sharp at any window size.
+        let viewport = Self::fit_viewport(logical_size);
+        let size = Self::pixel_size(viewport);
+        Self {
+            slots: vec![],
+            targets: [create_target(size), create_target(size)],
+            logical_size,
+            viewport,
+        }
+    }
+
+    // Appends a pass to the end of the chain.
+    pub fn push(&mut self, pass: impl PostPass, enabled: bool) {
+        let mut pass = Box::new(pass);
+        pass.resize(self.target_size());
+        self.slots.push(Slot { pass, enabled });
+    }
+
+    // The pass of type T, to read its parameters.
+    pub fn get<T: PostPass>(&self) -> Option<&T> {
+        self.slots.iter().find_map(|slot| slot.pass.as_any().downcast_ref::<T>())
+    }
+
+    // The pass of type T, to tune its parameters.
+    pub fn get_mut<T: PostPass>(&mut self) -> Option<&mut T> {
+        self.slots.iter_mut().find_map(|slot| slot.pass.as_any_mut().downcast_mut::<T>())
+    }
+
+    // Pass names in chain order, with their enabled flag.
+    pub fn passes(&self) -> impl Iterator<Item = (&'static str, bool)> + '_ {
+        self.slots.iter().map(|slot| (slot.pass.name(), slot.enabled))
+    }
+
+    // Passes are looked up by name for enabling, so it keeps working after reordering.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.slots.iter().any(|slot| slot.pass.name() == name && slot.enabled)
+    }
+
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) {
+        for slot in self.slots.iter_mut().filter(|slot| slot.pass.name() == name) {
+            slot.enabled = enabled;
+        }
+    }
+
+    // Moves the pass at `from` so it runs at position `to`.
+    pub fn move_pass(&mut self, from: usize, to: usize) {
+        if from < self.slots.len() && to < self.slots.len() {
+            let slot = self.slots.remove(from);
+            self.slots.insert(to, slot);
+        }
+    }
+
+    // Call once per frame. Recompiles any shader whose file changed (native debug
+    // builds only) and returns the compile errors of the ones that are broken.
+    pub fn reload_shaders(&mut self) -> Vec<String> {
+        // Release and web builds only ever use the embedded shaders, which can't change
+        if !HOT_RELOAD {
+            return vec![];
+        }
+        let mut errors = vec![];
+        for slot in self.slots.iter_mut() {
+            for shader in slot.pass.shaders_mut() {
+                shader.reload_if_changed();
+                if let Some(error) = shader.error() {
+                    errors.push(format!("{}: {}", shader.file(), error));
+                }
+            }
+        }
+        errors
+    }
+
+    // Largest rect with the logical aspect ratio that fits in the window, centred.
+    fn fit_viewport(logical_size: Vec2) -> Rect {
+        let (sw, sh) = (screen_width(), screen_height());
+        let scale = (sw / logical_size.x).min(sh / logical_size.y);
+        let size = logical_size * scale;
+        Rect::new((sw - size.x) / 2.0, (sh - size.y) / 2.0, size.x, size.y)
+    }
+
+    fn pixel_size(viewport: Rect) -> Vec2 {
+        vec2(viewport.w.round().max(1.0), viewport.h.round().max(1.0))
+    }
+
+    fn target_size(&self) -> Vec2 {
+        self.targets[0].texture.size()
+    }
+
+    // Call once per frame before begin(). Follows window / browser canvas resizes:
+    // recomputes the viewport and recreates the targets if their pixel size changed.
+    pub fn fit_to_window(&mut self) {
+        self.viewport = Self::fit_viewport(self.logical_size);
+        let size = Self::pixel_size(self.viewport);
+        if size != self.target_size() {
+            self.targets = [create_target(size), create_target(size)];
+            for slot in self.slots.iter_mut() {
+                slot.pass.resize(size);
+            }
+        }
+    }
+
+    // Call BEFORE drawing anything in the frame.
+    // Redirects all draw calls to the first off-screen target,
+    // with the camera mapping the logical world onto it.
+    // `shake` moves (logical units) and tilts (degrees) the camera, for screen shake.
+    pub fn begin(&self, shake: (Vec2, f32)) {
+        let (offset, tilt) = shake;
+        set_camera(&Camera2D {
+            zoom: vec2(2.0 / self.logical_size.x, 2.0 / self.logical_size.y),
+            target: self.logical_size / 2.0 + offset,
+            rotation: tilt,
+            render_target: Some(self.targets[0].clone()),
+            ..Default::default()
+        });
+
+        // A shaken camera uncovers the target's edges; keep them black, not last frame's
+        clear_background(BLACK);
+    }
+
+    // Call AFTER drawing everything in the frame.
+    // Runs the enabled passes, then draws the result into the viewport on the real screen.
+    pub fn end(&self) {
+        let size = self.target_size();
+        let mut current = 0;
+        for slot in self.slots.iter().filter(|slot| slot.enabled) {
+            let next = 1 - current;
+            slot.pass.apply(&self.targets[current].texture, &self.targets[next], size);
+            current = next;
+        }
+
+        // Switch back to the real screen; whatever the viewport doesn't cover stays black
+        set_default_camera();
+        clear_background(BLACK);
+        draw_texture_ex(
+            &self.targets[current].texture,
+            self.viewport.x, self.viewport.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(self.viewport.size()),
+                ..Default::default()
+            },
+        );
+    }
+}
+
+// --- COLOR GRADING ---
+
+const GRADE_FRAGMENT: &str = include_str!("../assets/shaders/grade.frag");
+
+// Contrast / saturation / tint. Neutral values: 1.0, 1.0, white.
+pub struct ColorGradePass {
+    pub contrast: f32,
+    pub saturation: f32,
+    pub tint: Color,
+    shader: PassShader,
+}
+
+impl ColorGradePass {
+    pub fn new() -> Self {
+        Self {
+            // A warm, slightly washed-out default, like an old arcade monitor
+            contrast: 1.1,
+            saturation: 0.85,
+            tint: Color::new(1.05, 0.97, 0.88, 1.0),
+            shader: PassShader::new("grade.frag", GRADE_FRAGMENT, &["Contrast", "Saturation", "TintR", "TintG", "TintB"], &[]),
+        }
+    }
+}
+
+impl PostPass for ColorGradePass {
+    fn name(&self) -> &'static str { "Color grading" }
+
+    fn apply(&self, input: &Texture2D, output: &RenderTarget, size: Vec2) {
+        self.shader.material().set_uniform("Contrast", self.contrast);
+        self.shader.material().set_uniform("Saturation", self.saturation);
+        self.shader.material().set_uniform("TintR", self.tint.r);
+        self.shader.material().set_uniform("TintG", self.tint.g);
+        self.shader.material().set_uniform("TintB", self.tint.b);
+        blit(input, output, size, Some(self.shader.material()));
+    }
+
+    fn shaders_mut(&mut self) -> Vec<&mut PassShader> { vec![&mut self.shader] }
+
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+// --- PIXELATION ---
+
+const PIXELATE_FRAGMENT: &str = include_str!("../assets/shaders/pixelate.frag");
+
+// Chunky low-res look. `pixel_size` is the block size in screen pixels.
+pub struct PixelatePass {
+    pub pixel_size: f32,
+    shader: PassShader,
+}
+
+impl PixelatePass {
+    pub fn new() -> Self {
+        Self {
+            pixel_size: 4.0,
+            shader: PassShader::new("pixelate.frag", PIXELATE_FRAGMENT, &["PixelSize", "Width", "Height"], &[]),
+        }
+    }
+}
+
+impl PostPass for PixelatePass {
+    fn name(&self) -> &'static str { "Pixelate" }
+
+    fn apply(&self, input: &Texture2D, output: &RenderTarget, size: Vec2) {
+        self.shader.material().set_uniform("PixelSize", self.pixel_size.max(1.0));
+        self.shader.material().set_uniform("Width", size.x);
+        self.shader.material().set_uniform("Height", size.y);
+        blit(input, output, size, Some(self.shader.material()));
+    }
+
+    fn shaders_mut(&mut self) -> Vec<&mut PassShader> { vec![&mut self.shader] }
+
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
+
+// --- MOTION BLUR ---
+
+const MOTION_BLUR_FRAGMENT: &str = include_str!("../assets/shaders/motion_blur.frag");
+
+// Blends each frame with the previous output, leaving trails behind moving things.
+// `amount` is how much of the previous frame survives (0 = none, 0.9 = long smears).
+pub struct MotionBlurPass {
+    pub amount: f32,
+    shader: PassShader,
+    // Last frame's output
+    history: RenderTarget,
+}
+
+impl MotionBlurPass {
+    pub fn new() -> Self {
+        Self {
+            amount: 0.5,
+            shader: PassShader::new("motion_blur.frag", MOTION_BLUR_FRAGMENT, &["Amount"], &["History"]),
+            history: create_target(vec2(1.0, 1.0)),
+        }
+    }
+}
+
+impl PostPass for MotionBlurPass {
+    fn name(&self) -> &'static str { "Motion blur" }
+
+    fn apply(&self, input: &Texture2D, output: &RenderTarget, size: Vec2) {
+        self.shader.material().set_uniform("Amount", self.amount.clamp(0.0, 0.95));
+        self.shader.material().set_texture("History", self.history.texture.clone());
+        blit(input, output, size, Some(self.shader.material()));
+
+        // Remember this frame for the next one
+        blit(&output.texture, &self.history, size, None);
+    }
+
+    fn resize(&mut self, size: Vec2) {
+        self.history = create_target(size);
+    }
+
+    fn shaders_mut(&mut self) -> Vec<&mut PassShader> { vec![&mut self.shader] }
+
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
@@ -0,0 +1,311 @@
+use std::fmt;
+use macroquad::math::{vec2, Vec2};
+use crate::rules::RuleSet;
+use crate::world::Input;
+
+// Replay file layout (all numbers little-endian):
+//
+//   "CACR"           magic
+//   u8               format version
+//   u64              run seed
+//   f32, f32         playfield width, height
+//   u8               rule set (see RuleSet::id)
+//   u32              number of input runs
+//   [u8, i8, u16] * n
+//                    input bits, turn, and how many consecutive steps they were held
+//
+// Inputs are stored per fixed simulation step, run-length encoded.
+// Players hold the same keys for long stretches, so a whole run is usually a few KB.
+const MAGIC: &[u8; 4] = b"CACR";
+const VERSION: u8 = 1;
+
+// Longest replay that will load, in simulation steps: 8 hours at 120 Hz.
+// A corrupt or hand-made file can't make us allocate more than this.
+const MAX_STEPS: usize = 8 * 60 * 60 * 120;
+
+// Bit layout of one step's Input
+const THRUST: u8     = 1 << 0;
+const FIRE: u8       = 1 << 1;
+const HYPERSPACE: u8 = 1 << 2;
+
+// One step's input as stored: the button bits, and the turn in -127..127
+type Packed = (u8, i8);
+
+fn pack(input: &Input) -> Packed {
+    let mut bits = 0;
+    if input.thrust     { bits |= THRUST; }
+    if input.fire       { bits |= FIRE; }
+    if input.hyperspace { bits |= HYPERSPACE; }
+    let turn = (input.turn.clamp(-1.0, 1.0) * 127.0).round() as i8;
+    (bits, turn)
+}
+
+fn unpack((bits, turn): Packed) -> Input {
+    Input {
+        turn: turn as f32 / 127.0,
+        thrust: bits & THRUST != 0,
+        fire: bits & FIRE != 0,
+        hyperspace: bits & HYPERSPACE != 0,
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    NotAReplay,
+    UnsupportedVersion(u8),
+    UnknownRuleSet(u8),
+    Truncated,
+    TooLong,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "{e}"),
+            ReplayError::NotAReplay => write!(f, "not a replay file"),
+            ReplayError::UnsupportedVersion(v) => write!(f, "unsupported replay version {v}"),
+            ReplayError::UnknownRuleSet(id) => write!(f, "unknown rule set {id}"),
+            ReplayError::Truncated => write!(f, "replay file is truncated"),
+            ReplayError::TooLong => write!(f, "replay is too long"),
+        }
+    }
+}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(e: std::io::Error) -> Self {
+        ReplayError::Io(e)
+    }
+}
+
+// Everything needed to re-run a game exactly: the seed, the playfield size,
+// the rules, and the input for every simulation step.
+pub struct Replay {
+    pub seed: u64,
+    pub bounds: Vec2,
+    pub rule_set: RuleSet,
+    inputs: Vec<Packed>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, bounds: Vec2, rule_set: RuleSet) -> Self {
+        Self { seed, bounds, rule_set, inputs: vec![] }
+    }
+
+    // Records the input of the next simulation step, and returns it the way
+    // playback will hand it back (the turn is stored with less precision).
+    // Step the world with the returned one, so the replay can't drift from the run.
+    pub fn push(&mut self, input: &Input) -> Input {
+        let packed = pack(input);
+        self.inputs.push(packed);
+        unpack(packed)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // Collapse consecutive identical steps into (input, count) runs
+        let mut runs: Vec<(Packed, u16)> = vec![];
+        for &packed in &self.inputs {
+            match runs.last_mut() {
+                Some((last, count)) if *last == packed && *count < u16::MAX => *count += 1,
+                _ => runs.push((packed, 1)),
+            }
+        }
+
+        let mut out = Vec::with_capacity(26 + runs.len() * 4);
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        out.extend_from_slice(&self.bounds.x.to_le_bytes());
+        out.extend_from_slice(&self.bounds.y.to_le_bytes());
+        out.push(self.rule_set.id());
+        out.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+        for ((bits, turn), count) in runs {
+            out.push(bits);
+            out.push(turn as u8);
+            out.extend_from_slice(&count.to_le_bytes());
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let mut reader = Reader { bytes };
+
+        if reader.take(4)? != MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+        let version = reader.take(1)?[0];
+        if version != VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let seed = u64::from_le_bytes(reader.array()?);
+        let bounds = vec2(f32::from_le_bytes(reader.array()?), f32::from_le_bytes(reader.array()?));
+        let rule_set_id = reader.take(1)?[0];
+        let rule_set = RuleSet::from_id(rule_set_id).ok_or(ReplayError::UnknownRuleSet(rule_set_id))?;
+        let run_count = u32::from_le_bytes(reader.array()?) as usize;
+        // Each run is 4 bytes; don't believe a count the file can't back up
+        if reader.bytes.len() < run_count.saturating_mul(4) {
+            return Err(ReplayError::Truncated);
+        }
+
+        let mut inputs = vec![];
+        for _ in 0..run_count {
+            let bits = reader.take(1)?[0];
+            let turn = reader.take(1)?[0] as i8;
+            let count = u16::from_le_bytes(reader.array()?) as usize;
+            if inputs.len() + count > MAX_STEPS {
+                return Err(ReplayError::TooLong);
+            }
+            inputs.extend(std::iter::repeat_n((bits, turn), count));
+        }
+
+        Ok(Self { seed, bounds, rule_set, inputs })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: &std::path::Path) -> Result<(), ReplayError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: &std::path::Path) -> Result<Self, ReplayError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    // Browsers have no file system to write to, so the replay is handed to
+    // the page as a download instead (see the cacaroids plugin in index.html).
+    #[cfg(target_arch = "wasm32")]
+    pub fn download(&self) {
+        extern "C" {
+            fn cacaroids_download_replay(ptr: *const u8, len: u32);
+        }
+        let bytes = self.to_bytes();
+        unsafe { cacaroids_download_replay(bytes.as_ptr(), bytes.len() as u32) };
+    }
+}
+
+// Feeds a recorded Replay back into the world one step at a time.
+pub struct Playback {
+    replay: Replay,
+    cursor: usize,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, cursor: 0 }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    // Input for the next step. Once the recording runs out, the ship just drifts.
+    pub fn next_input(&mut self) -> Input {
+        let packed = self.replay.inputs.get(self.cursor).copied().unwrap_or((0, 0));
+        self.cursor += 1;
+        unpack(packed)
+    }
+}
+
+// Minimal cursor over a byte slice for from_bytes().
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ReplayError> {
+        if self.bytes.len() < n {
+            return Err(ReplayError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(turn: f32, thrust: bool, fire: bool) -> Input {
+        Input { turn, thrust, fire, hyperspace: false }
+    }
+
+    fn play_all(replay: Replay, steps: usize) -> Vec<Input> {
+        let mut playback = Playback::new(replay);
+        (0..steps).map(|_| playback.next_input()).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut replay = Replay::new(1234, vec2(1280.0, 720.0), RuleSet::Daily);
+        let mut recorded = vec![];
+        // A run longer than a u16 count can hold, then some variety
+        for _ in 0..70_000 {
+            recorded.push(replay.push(&input(0.0, true, false)));
+        }
+        for i in 0..50 {
+            recorded.push(replay.push(&input(i as f32 / 25.0 - 1.0, i % 3 == 0, i % 2 == 0)));
+        }
+
+        let loaded = Replay::from_bytes(&replay.to_bytes()).unwrap();
+        assert_eq!(loaded.seed, 1234);
+        assert_eq!(loaded.bounds, vec2(1280.0, 720.0));
+        assert_eq!(loaded.rule_set, RuleSet::Daily);
+        assert_eq!(play_all(loaded, recorded.len()), recorded);
+    }
+
+    #[test]
+    fn push_quantizes_like_playback() {
+        let mut replay = Replay::new(1, vec2(1280.0, 720.0), RuleSet::Classic);
+        let pushed = replay.push(&input(0.3337, false, false));
+        assert!((pushed.turn - 0.3337).abs() < 1.0 / 127.0);
+        assert_eq!(play_all(replay, 1), [pushed]);
+    }
+
+    #[test]
+    fn overlong_replays_are_rejected() {
+        let runs = MAX_STEPS / u16::MAX as usize + 1;
+        let mut bytes = Replay::new(1, vec2(1280.0, 720.0), RuleSet::Classic).to_bytes();
+        bytes.truncate(22);
+        bytes.extend_from_slice(&(runs as u32).to_le_bytes());
+        for _ in 0..runs {
+            bytes.extend_from_slice(&[FIRE, 0]);
+            bytes.extend_from_slice(&u16::MAX.to_le_bytes());
+        }
+        assert!(matches!(Replay::from_bytes(&bytes), Err(ReplayError::TooLong)));
+    }
+
+    #[test]
+    fn bad_files_are_rejected() {
+        let good = Replay::new(1, vec2(1280.0, 720.0), RuleSet::Classic).to_bytes();
+
+        let mut wrong_magic = good.clone();
+        wrong_magic[0] = b'X';
+        assert!(matches!(Replay::from_bytes(&wrong_magic), Err(ReplayError::NotAReplay)));
+
+        assert!(matches!(Replay::from_bytes(&good[..10]), Err(ReplayError::Truncated)));
+        assert!(matches!(Replay::from_bytes(b"CA"), Err(ReplayError::Truncated)));
+
+        let mut future = good.clone();
+        future[4] = VERSION + 1;
+        assert!(matches!(Replay::from_bytes(&future), Err(ReplayError::UnsupportedVersion(_))));
+
+        // A run count the file doesn't have the bytes for
+        let mut inflated = good.clone();
+        inflated[22..26].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(Replay::from_bytes(&inflated), Err(ReplayError::Truncated)));
+
+        let mut unknown_rules = good.clone();
+        unknown_rules[21] = 99;
+        assert!(matches!(Replay::from_bytes(&unknown_rules), Err(ReplayError::UnknownRuleSet(99))));
+    }
+}
@@ -0,0 +1,205 @@
+// Tunable game rules. Everything that decides difficulty lives here
+// instead of being scattered as constants, so a game mode is just a different Rules value.
+
+// Relative chance of each asteroid size when a wave spawns.
+// { big: 1, medium: 0, small: 0 } = all big (classic), { big: 2, medium: 1, small: 0 } = 2/3 big, 1/3 medium.
+#[derive(Clone, Copy, Debug)]
+pub struct SizeMix {
+    pub big: u32,
+    pub medium: u32,
+    pub small: u32,
+}
+
+impl SizeMix {
+    pub const ALL_BIG: SizeMix = SizeMix { big: 1, medium: 0, small: 0 };
+}
+
+// One row of the wave table.
+#[derive(Clone, Copy, Debug)]
+pub struct Wave {
+    // How many asteroids spawn at the start of the wave
+    pub count: usize,
+    // Multiplier on AsteroidSize::speed() for every asteroid in the wave (children included)
+    pub speed_scale: f32,
+    pub size_mix: SizeMix,
+}
+
+// Difficulty curve. Waves are numbered from 1.
+// Past the end of the list the last row keeps getting harder by the growth values,
+// up to the caps, so the game never runs out of waves.
+#[derive(Clone, Debug)]
+pub struct WaveTable {
+    pub waves: Vec<Wave>,
+    pub count_growth: usize,
+    pub speed_growth: f32,
+    pub max_count: usize,
+    pub max_speed_scale: f32,
+}
+
+impl WaveTable {
+    pub fn wave(&self, number: u32) -> Wave {
+        let index = number.saturating_sub(1) as usize;
+        if let Some(wave) = self.waves.get(index) {
+            return *wave;
+        }
+
+        let last = *self.waves.last().expect("wave table needs at least one wave");
+        let extra = index + 1 - self.waves.len();
+        Wave {
+            count: (last.count + self.count_growth * extra).min(self.max_count),
+            speed_scale: (last.speed_scale + self.speed_growth * extra as f32).min(self.max_speed_scale),
+            size_mix: last.size_mix,
+        }
+    }
+}
+
+impl Default for WaveTable {
+    // Starts like the original round (5 big asteroids) and ramps up from there.
+    fn default() -> Self {
+        Self {
+            waves: vec![
+                Wave { count: 5, speed_scale: 1.0, size_mix: SizeMix::ALL_BIG },
+                Wave { count: 6, speed_scale: 1.1, size_mix: SizeMix::ALL_BIG },
+                Wave { count: 7, speed_scale: 1.2, size_mix: SizeMix { big: 4, medium: 1, small: 0 } },
+                Wave { count: 8, speed_scale: 1.3, size_mix: SizeMix { big: 3, medium: 1, small: 0 } },
+                Wave { count: 9, speed_scale: 1.4, size_mix: SizeMix { big: 3, medium: 2, small: 1 } },
+            ],
+            count_growth: 1,
+            speed_growth: 0.1,
+            max_count: 14,
+            max_speed_scale: 2.2,
+        }
+    }
+}
+
+// When the player earns an extra ship.
+#[derive(Clone, Debug)]
+pub enum ExtraLifeSchedule {
+    // One ship every N points (N, 2N, 3N, ...)
+    Every(u32),
+    // One ship at each listed score, e.g. vec![5_000, 20_000, 50_000].
+    // For modes with a hand-tuned schedule, like the daily challenge.
+    At(Vec<u32>),
+}
+
+impl ExtraLifeSchedule {
+    // How many thresholds the score passed going from `old` to `new` (old < t <= new).
+    pub fn lives_earned(&self, old: u32, new: u32) -> u32 {
+        match self {
+            ExtraLifeSchedule::Every(0) => 0,
+            ExtraLifeSchedule::Every(n) => new / n - old / n,
+            ExtraLifeSchedule::At(thresholds) => {
+                thresholds.iter().filter(|&&t| old < t && t <= new).count() as u32
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Rules {
+    pub waves: WaveTable,
+
+    // Ships the player starts with. Losing the last one ends the run.
+    pub starting_lives: u32,
+
+    // Seconds after a respawn during which asteroids pass straight through the player
+    pub invulnerable_time: f32,
+
+    pub extra_lives: ExtraLifeSchedule,
+
+    // Seconds before hyperspace can be used again
+    pub hyperspace_cooldown: f32,
+
+    // Chance (0..1) that a jump destroys the ship on re-entry, like the arcade original
+    pub hyperspace_failure_chance: f32,
+
+    // When true, jumps only land where no asteroid is within the safe-spawn radius.
+    // When false (arcade), anywhere goes — including right next to a poop.
+    pub hyperspace_safe: bool,
+
+    // Average seconds between saucer appearances
+    pub saucer_interval: f32,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            waves: WaveTable::default(),
+            starting_lives: 3,
+            invulnerable_time: 3.0,
+            extra_lives: ExtraLifeSchedule::Every(10_000),
+            hyperspace_cooldown: 1.0,
+            hyperspace_failure_chance: 0.1,
+            hyperspace_safe: false,
+            saucer_interval: 15.0,
+        }
+    }
+}
+
+// The named Rules presets a run can use. Replays store which one, so playback
+// steps the world under the same rules the run was played with.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RuleSet {
+    Classic,
+    // Same waves, but a fixed handful of extra ships instead of one every
+    // 10,000 points, so everyone's daily run tops out at the same number of lives.
+    Daily,
+}
+
+impl RuleSet {
+    pub fn rules(self) -> Rules {
+        match self {
+            RuleSet::Classic => Rules::default(),
+            RuleSet::Daily => Rules {
+                extra_lives: ExtraLifeSchedule::At(vec![10_000, 30_000, 60_000, 100_000]),
+                ..Rules::default()
+            },
+        }
+    }
+
+    // For the replay file
+    pub fn id(self) -> u8 {
+        match self {
+            RuleSet::Classic => 0,
+            RuleSet::Daily   => 1,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(RuleSet::Classic),
+            1 => Some(RuleSet::Daily),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_n_points() {
+        let every = ExtraLifeSchedule::Every(10_000);
+        assert_eq!(every.lives_earned(0, 9_999), 0);
+        assert_eq!(every.lives_earned(9_990, 10_000), 1);
+        assert_eq!(every.lives_earned(10_000, 10_050), 0);
+        // One big score (say, a saucer ram) can cross several thresholds at once
+        assert_eq!(every.lives_earned(9_000, 41_000), 4);
+    }
+
+    #[test]
+    fn every_zero_never_awards() {
+        assert_eq!(ExtraLifeSchedule::Every(0).lives_earned(0, u32::MAX), 0);
+    }
+
+    #[test]
+    fn at_listed_scores() {
+        let at = ExtraLifeSchedule::At(vec![5_000, 20_000, 50_000]);
+        assert_eq!(at.lives_earned(0, 4_999), 0);
+        assert_eq!(at.lives_earned(4_999, 5_000), 1);
+        assert_eq!(at.lives_earned(5_000, 19_999), 0);
+        assert_eq!(at.lives_earned(4_000, 60_000), 3);
+        assert_eq!(at.lives_earned(50_000, 1_000_000), 0);
+    }
+}
@@ -0,0 +1,157 @@
+use macroquad::prelude::*;
+use macroquad::rand::RandGenerator;
+use crate::torus;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SaucerSize {
+    Large,
+    Small,
+}
+
+impl SaucerSize {
+    pub fn radius(&self) -> f32 {
+        match self {
+            SaucerSize::Large => 28.0,
+            SaucerSize::Small => 16.0,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        match self {
+            SaucerSize::Large => 110.0,
+            SaucerSize::Small => 160.0,
+        }
+    }
+
+    pub fn score(&self) -> u32 {
+        match self {
+            SaucerSize::Large => 200,
+            SaucerSize::Small => 1000,
+        }
+    }
+
+    // Seconds between shots
+    pub fn fire_interval(&self) -> f32 {
+        match self {
+            SaucerSize::Large => 1.2,
+            SaucerSize::Small => 0.9,
+        }
+    }
+}
+
+// Seconds between course changes (random within this range)
+const TURN_INTERVAL: (f32, f32) = (0.8, 2.0);
+
+// Small saucer aim: random spread (radians) at score 0, shrinking to
+// MIN_SPREAD by the time the score reaches PERFECT_AIM_SCORE.
+const MAX_SPREAD: f32 = 0.6;
+const MIN_SPREAD: f32 = 0.04;
+const PERFECT_AIM_SCORE: f32 = 40_000.0;
+
+// Flying saucer that crosses the screen once, zig-zagging and shooting.
+// Large saucers shoot at random; small ones aim at the player.
+pub struct Saucer {
+    pub pos: Vec2,
+    pub vel: Vec2,
+    pub size: SaucerSize,
+    pub alive: bool,
+    turn_timer: f32,
+    fire_timer: f32,
+
+    // Distance left to travel horizontally before it leaves the screen
+    travel_left: f32,
+
+    // Position at the end of the previous simulation step, used for interpolation.
+    pub prev_pos: Vec2,
+}
+
+impl Saucer {
+    // Enters from the left or right edge at a random height, heading across.
+    pub fn new(size: SaucerSize, bounds: Vec2, rng: &RandGenerator) -> Self {
+        let from_left = rng.gen_range(0, 2) == 0;
+        let x = if from_left { 0.0 } else { bounds.x };
+        let pos = vec2(x, rng.gen_range(0.0, bounds.y));
+        let dir_x = if from_left { 1.0 } else { -1.0 };
+
+        Self {
+            pos,
+            vel: vec2(dir_x * size.speed(), 0.0),
+            size,
+            alive: true,
+            turn_timer: rng.gen_range(TURN_INTERVAL.0, TURN_INTERVAL.1),
+            fire_timer: size.fire_interval(),
+            travel_left: bounds.x,
+            prev_pos: pos,
+        }
+    }
+
+    // Advances the saucer by one fixed simulation step of `dt` seconds.
+    // Wraps vertically, but leaves for good once it has crossed the screen horizontally.
+    // Returns true when it's time to fire.
+    pub fn update(&mut self, dt: f32, bounds: Vec2, rng: &RandGenerator) -> bool {
+        self.prev_pos = self.pos;
+
+        // Every so often pick a new vertical heading: up, straight or down
+        self.turn_timer -= dt;
+        if self.turn_timer <= 0.0 {
+            self.turn_timer = rng.gen_range(TURN_INTERVAL.0, TURN_INTERVAL.1);
+            let heading = rng.gen_range(0, 3) as f32 - 1.0;
+            self.vel.y = heading * self.size.speed() * 0.6;
+        }
+
+        self.pos += self.vel * dt;
+        self.travel_left -= self.vel.x.abs() * dt;
+        if self.travel_left <= 0.0 {
+            self.alive = false;
+        }
+
+        // Vertical wrap only (prev_pos follows so interpolation doesn't streak)
+        let before = self.pos;
+        self.pos.y = self.pos.y.rem_euclid(bounds.y);
+        self.prev_pos += self.pos - before;
+
+        self.fire_timer -= dt;
+        if self.fire_timer <= 0.0 {
+            self.fire_timer = self.size.fire_interval();
+            return true;
+        }
+        false
+    }
+
+    // Direction of the next shot. Large saucers spray randomly; small ones aim at
+    // `target`, with a random error that shrinks as the player's `score` grows.
+    // Aims the short way round, so it will happily shoot across a screen edge.
+    pub fn aim(&self, target: Vec2, score: u32, bounds: Vec2, rng: &RandGenerator) -> Vec2 {
+        match self.size {
+            SaucerSize::Large => Vec2::from_angle(rng.gen_range(0.0_f32, std::f32::consts::TAU)),
+            SaucerSize::Small => {
+                let skill = (score as f32 / PERFECT_AIM_SCORE).min(1.0);
+                let spread = MAX_SPREAD + (MIN_SPREAD - MAX_SPREAD) * skill;
+                let to_target = torus::delta(self.pos, target, bounds).normalize_or_zero();
+                let angle = to_target.to_angle() + rng.gen_range(-spread, spread);
+                Vec2::from_angle(angle)
+            }
+        }
+    }
+
+    // No sprite for these yet, so they're drawn from shapes:
+    // a flat hull with a glass dome on top.
+    // Saucers only wrap vertically, so only top/bottom ghosts are drawn.
+    pub fn draw(&self, alpha: f32, bounds: Vec2) {
+        let pos = self.prev_pos.lerp(self.pos, alpha);
+        let r = self.size.radius();
+
+        torus::for_each_ghost(pos, r, bounds, |ghost| {
+            if ghost.x != pos.x {
+                return;
+            }
+            draw_ellipse(ghost.x, ghost.y - r * 0.25, r * 0.5, r * 0.45, 0.0, Color::new(0.6, 0.9, 1.0, 0.8));
+            draw_ellipse(ghost.x, ghost.y, r, r * 0.4, 0.0, Color::new(0.45, 0.5, 0.55, 1.0));
+            draw_ellipse_lines(ghost.x, ghost.y, r, r * 0.4, 0.0, 2.0, Color::new(0.8, 0.85, 0.9, 1.0));
+        });
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.size.radius()
+    }
+}
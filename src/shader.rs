@@ -1,132 +1,159 @@
+use std::any::Any;
 use macroquad::prelude::*;
+use crate::postfx::{blit, PassShader, PostPass};
 
-// The vertex shader just passes UV coordinates through to the fragment shader.
-// No transformation magic here — all the visual work happens in the fragment shader.
-const CRT_VERTEX: &str = "
-#version 100
-attribute vec3 position;
-attribute vec2 texcoord;
-varying lowp vec2 uv;
-uniform mat4 Model;
-uniform mat4 Projection;
-
-void main() {
-    gl_Position = Projection * Model * vec4(position, 1.0);
-    uv = texcoord;
-}
-";
+// The CRT effect, as one pass of the post-processing chain (see postfx.rs).
 
 // The fragment shader applies all CRT effects:
 // 1. Barrel distortion (curved screen)
 // 2. Chromatic aberration (color fringing)
 // 3. Scanlines
 // 4. Vignette (dark edges)
-const CRT_FRAGMENT: &str = "
-#version 100
-precision lowp float;
-varying vec2 uv;
-uniform sampler2D Texture;
-
-// Bends UV coords to simulate a curved CRT tube.
-// Increase the 0.15 multiplier for more extreme curvature.
-vec2 curve(vec2 uv) {
-    uv = (uv - 0.5) * 2.0;
-    uv *= 1.0 + dot(uv.yx, uv.yx) * 0.04;
-    uv = (uv / 2.0) + 0.5;
-    return uv;
+const CRT_FRAGMENT: &str = include_str!("../assets/shaders/crt.frag");
+
+// Every knob of the CRT shader. Values map 1:1 to the fragment shader uniforms.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CrtSettings {
+    // Barrel distortion amount (0 = flat screen)
+    pub curvature: f32,
+    // Horizontal R/B offset in UV units (0 = no color fringing)
+    pub aberration: f32,
+    // Scanline density across the screen height
+    pub scanline_frequency: f32,
+    // How much scanlines darken the image (0 = no scanlines)
+    pub scanline_strength: f32,
+    // Vignette falloff exponent (0 = no vignette)
+    pub vignette_exponent: f32,
+    // Final brightness multiplier
+    pub brightness: f32,
+}
+
+// Ready-made looks. Arcade is the original hard-coded look.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CrtPreset {
+    Off,
+    Subtle,
+    Arcade,
+    Extreme,
 }
 
-void main() {
-    vec2 curved_uv = curve(uv);
+impl CrtPreset {
+    const ALL: [CrtPreset; 4] = [CrtPreset::Off, CrtPreset::Subtle, CrtPreset::Arcade, CrtPreset::Extreme];
+
+    pub fn settings(&self) -> CrtSettings {
+        match self {
+            // Plain image: every effect at its neutral value
+            CrtPreset::Off => CrtSettings {
+                curvature: 0.0,
+                aberration: 0.0,
+                scanline_frequency: 800.0,
+                scanline_strength: 0.0,
+                vignette_exponent: 0.0,
+                brightness: 1.0,
+            },
+            // Lighter scanlines for small screens, where the arcade ones get muddy
+            CrtPreset::Subtle => CrtSettings {
+                curvature: 0.02,
+                aberration: 0.0008,
+                scanline_frequency: 800.0,
+                scanline_strength: 0.06,
+                vignette_exponent: 0.08,
+                brightness: 1.08,
+            },
+            CrtPreset::Arcade => CrtSettings {
+                curvature: 0.04,
+                aberration: 0.0015,
+                scanline_frequency: 800.0,
+                scanline_strength: 0.15,
+                vignette_exponent: 0.15,
+                brightness: 1.2,
+            },
+            CrtPreset::Extreme => CrtSettings {
+                curvature: 0.12,
+                aberration: 0.004,
+                scanline_frequency: 600.0,
+                scanline_strength: 0.3,
+                vignette_exponent: 0.3,
+                brightness: 1.35,
+            },
+        }
+    }
 
-    // Anything outside 0..1 after curving is the black border around the screen
-    if (curved_uv.x < 0.0 || curved_uv.x > 1.0 ||
-        curved_uv.y < 0.0 || curved_uv.y > 1.0) {
-        gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
-        return;
+    pub fn name(&self) -> &'static str {
+        match self {
+            CrtPreset::Off     => "Off",
+            CrtPreset::Subtle  => "Subtle",
+            CrtPreset::Arcade  => "Arcade",
+            CrtPreset::Extreme => "Extreme",
+        }
     }
 
-    // Chromatic aberration: sample R/G/B at slightly different UV offsets.
-    // Mimics the color misalignment of old CRT phosphor guns.
-    float aberration = 0.0015;
-    float r = texture2D(Texture, curved_uv + vec2( aberration, 0.0)).r;
-    float g = texture2D(Texture, curved_uv).g;
-    float b = texture2D(Texture, curved_uv - vec2( aberration, 0.0)).b;
-    float a = texture2D(Texture, curved_uv).a;
-    vec4 color = vec4(r, g, b, a);
-
-    // Scanlines: creates horizontal dark bands like a real CRT.
-    // 800.0 controls line frequency — raise for denser lines.
-    // 0.15 controls darkness — raise for more contrast.
-    float scanline = sin(curved_uv.y * 800.0) * 0.15;
-    color.rgb -= scanline;
-
-    // Vignette: darkens screen edges, brighter in the center.
-    // Lower the pow() exponent for a stronger, wider dark border.
-    float vignette = 16.0 * curved_uv.x * curved_uv.y *
-                     (1.0 - curved_uv.x) * (1.0 - curved_uv.y);
-    vignette = clamp(pow(vignette, 0.15), 0.0, 1.0);
-    color.rgb *= vignette;
-
-    // Slight brightness boost to compensate for the overall darkening
-    color.rgb *= 1.2;
-
-    gl_FragColor = color;
+    // The preset after this one, wrapping around. Handy for a "cycle" key.
+    pub fn next(&self) -> CrtPreset {
+        let i = Self::ALL.iter().position(|p| p == self).unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
 }
-";
 
-pub struct CrtEffect {
-    pub material: Material,
-    pub render_target: RenderTarget,
+impl Default for CrtSettings {
+    fn default() -> Self {
+        CrtPreset::Arcade.settings()
+    }
 }
 
-impl CrtEffect {
+pub struct CrtPass {
+    shader: PassShader,
+
+    // Current shader parameters, uploaded as uniforms every frame
+    settings: CrtSettings,
+}
+
+impl CrtPass {
     pub fn new() -> Self {
-        // RenderTarget is an off-screen texture we draw the whole game into first.
-        // Then we draw THAT texture to the real screen with the CRT shader applied.
-        let render_target = render_target(1280, 720);
-        render_target.texture.set_filter(FilterMode::Linear);
-
-        let material = load_material(
-            ShaderSource::Glsl {
-                vertex: CRT_VERTEX,
-                fragment: CRT_FRAGMENT,
-            },
-            MaterialParams::default(),
-        ).unwrap();
+        let uniforms = &[
+            "Curvature",
+            "Aberration",
+            "ScanlineFrequency",
+            "ScanlineStrength",
+            "VignetteExponent",
+            "Brightness",
+        ];
+        let shader = PassShader::new("crt.frag", CRT_FRAGMENT, uniforms, &[]);
+
+        Self { shader, settings: CrtSettings::default() }
+    }
 
-        Self { material, render_target }
+    pub fn settings(&self) -> CrtSettings {
+        self.settings
     }
 
-    // Call BEFORE drawing anything in the frame.
-    // Redirects all draw calls to the off-screen render target.
-    pub fn begin(&self) {
-        set_camera(&Camera2D {
-            zoom: vec2(2.0 / 1280.0, 2.0 / 720.0),
-            target: vec2(640.0, 360.0),
-            render_target: Some(self.render_target.clone()),
-            ..Default::default()
-        });
+    // Takes effect from the next frame; nothing needs recompiling.
+    pub fn set_settings(&mut self, settings: CrtSettings) {
+        self.settings = settings;
     }
 
-    // Call AFTER drawing everything in the frame.
-    // Applies the CRT shader and draws the result to the real screen.
-    pub fn end(&self) {
-        // Switch back to the real screen
-        set_default_camera();
-
-        gl_use_material(&self.material);
-        draw_texture_ex(
-            &self.render_target.texture,
-            0.0, 0.0,
-            WHITE,
-            DrawTextureParams {
-                dest_size: Some(vec2(screen_width(), screen_height())),
-                // Render targets are flipped vertically in OpenGL — flip_y fixes that
-                flip_y: false,
-                ..Default::default()
-            },
-        );
-        gl_use_default_material();
+    pub fn set_preset(&mut self, preset: CrtPreset) {
+        self.set_settings(preset.settings());
     }
-}
\ No newline at end of file
+}
+
+impl PostPass for CrtPass {
+    fn name(&self) -> &'static str { "CRT" }
+
+    fn apply(&self, input: &Texture2D, output: &RenderTarget, size: Vec2) {
+        let s = &self.settings;
+        self.shader.material().set_uniform("Curvature", s.curvature);
+        self.shader.material().set_uniform("Aberration", s.aberration);
+        self.shader.material().set_uniform("ScanlineFrequency", s.scanline_frequency);
+        self.shader.material().set_uniform("ScanlineStrength", s.scanline_strength);
+        self.shader.material().set_uniform("VignetteExponent", s.vignette_exponent);
+        self.shader.material().set_uniform("Brightness", s.brightness);
+        blit(input, output, size, Some(self.shader.material()));
+    }
+
+    fn shaders_mut(&mut self) -> Vec<&mut PassShader> { vec![&mut self.shader] }
+
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+}
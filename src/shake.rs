@@ -0,0 +1,56 @@
+use macroquad::prelude::*;
+
+// Trauma-based screen shake.
+//
+// Hits add "trauma" (0..1), which drains away over time. The shake itself is
+// trauma squared, so small hits give a barely-there nudge and big ones really rattle.
+// The result is an offset and a tilt for the scene camera (see PostChain::begin);
+// entity positions are never touched.
+
+// Trauma lost per second
+const DECAY: f32 = 1.6;
+
+// Camera movement at full trauma: logical units, and degrees of tilt
+const MAX_OFFSET: f32 = 18.0;
+const MAX_ANGLE: f32 = 2.5;
+
+pub struct ScreenShake {
+    trauma: f32,
+    // Runs while shaking; drives the wobble
+    time: f32,
+    // Off for players who find shaking uncomfortable
+    pub enabled: bool,
+}
+
+impl ScreenShake {
+    pub fn new() -> Self {
+        Self { trauma: 0.0, time: 0.0, enabled: true }
+    }
+
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.trauma = (self.trauma - DECAY * dt).max(0.0);
+        self.time += dt;
+    }
+
+    pub fn reset(&mut self) {
+        self.trauma = 0.0;
+    }
+
+    // Current camera offset (logical units) and tilt (degrees).
+    // A few out-of-step sine waves per axis: smooth, but never visibly repeating.
+    pub fn offset(&self) -> (Vec2, f32) {
+        if !self.enabled || self.trauma <= 0.0 {
+            return (Vec2::ZERO, 0.0);
+        }
+        let shake = self.trauma * self.trauma;
+        let t = self.time * 30.0;
+        let wobble = |a: f32, b: f32| ((t * a).sin() + (t * b).sin() * 0.5) / 1.5;
+        let offset = vec2(wobble(1.0, 2.3), wobble(1.3, 1.9)) * MAX_OFFSET * shake;
+        let angle = wobble(0.7, 2.9) * MAX_ANGLE * shake;
+        (offset, angle)
+    }
+}
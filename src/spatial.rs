@@ -0,0 +1,139 @@
+use macroquad::math::Vec2;
+
+// Uniform-grid broadphase for circle collisions.
+//
+// Every entity goes into the one cell that holds its centre. A query looks at every
+// cell that could hold something touching the query circle — its radius plus the
+// biggest radius inserted — and returns those entities as candidates for an exact check.
+//
+// The grid wraps around like the playfield: a query near the left edge also looks
+// at the cells along the right edge, and so on.
+//
+// Cells keep their Vecs between rebuilds, so after the first few steps
+// rebuilding and querying don't allocate.
+pub struct SpatialHash {
+    cell_size: f32,
+    // Actual cell dimensions: cell_size stretched so a whole number of cells
+    // fits the playfield exactly. Otherwise the last column/row would be a
+    // partial cell and wrapped coordinates would land in the wrong one.
+    cell_w: f32,
+    cell_h: f32,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+    max_radius: f32,
+}
+
+impl SpatialHash {
+    // `cell_size` should be around the size of the typical query (a big asteroid's
+    // diameter works well). Much smaller means many cells per query, much bigger
+    // means many candidates per cell.
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cell_w: cell_size,
+            cell_h: cell_size,
+            cols: 0,
+            rows: 0,
+            cells: vec![],
+            max_radius: 0.0,
+        }
+    }
+
+    // Clears the grid and refills it with `items`: (index, centre, radius) triples,
+    // where `index` is whatever the caller uses to find the entity again.
+    pub fn rebuild(&mut self, bounds: Vec2, items: impl Iterator<Item = (usize, Vec2, f32)>) {
+        self.cols = ((bounds.x / self.cell_size).ceil() as usize).max(1);
+        self.rows = ((bounds.y / self.cell_size).ceil() as usize).max(1);
+        self.cell_w = bounds.x / self.cols as f32;
+        self.cell_h = bounds.y / self.rows as f32;
+        self.cells.resize_with(self.cols * self.rows, Vec::new);
+        for cell in self.cells.iter_mut() {
+            cell.clear();
+        }
+
+        self.max_radius = 0.0;
+        for (index, pos, radius) in items {
+            let (col, row) = (self.col_of(pos.x), self.row_of(pos.y));
+            self.cells[row * self.cols + col].push(index);
+            self.max_radius = self.max_radius.max(radius);
+        }
+    }
+
+    // Appends to `out` the index of every entity that might overlap the circle at
+    // `pos` with `radius`. There can be false positives (do the exact check),
+    // never false negatives. Each index appears once, in ascending order.
+    pub fn query(&self, pos: Vec2, radius: f32, out: &mut Vec<usize>) {
+        out.clear();
+        if self.cells.is_empty() {
+            return;
+        }
+
+        let reach = radius + self.max_radius;
+        let (min_col, max_col) = Self::span(pos.x - reach, pos.x + reach, self.cell_w, self.cols);
+        let (min_row, max_row) = Self::span(pos.y - reach, pos.y + reach, self.cell_h, self.rows);
+
+        for row in min_row..=max_row {
+            let row = row.rem_euclid(self.rows as i32) as usize;
+            for col in min_col..=max_col {
+                let col = col.rem_euclid(self.cols as i32) as usize;
+                out.extend_from_slice(&self.cells[row * self.cols + col]);
+            }
+        }
+
+        // Keep the same order a plain loop over the entity list would give,
+        // so results don't depend on grid layout (and replays stay deterministic).
+        out.sort_unstable();
+    }
+
+    // Range of (unwrapped) cell coordinates covering min..max along one axis.
+    // Capped at the grid size so a huge query never visits the same cell twice.
+    fn span(min: f32, max: f32, cell: f32, count: usize) -> (i32, i32) {
+        let first = (min / cell).floor() as i32;
+        let last = (max / cell).floor() as i32;
+        (first, last.min(first + count as i32 - 1))
+    }
+
+    fn col_of(&self, x: f32) -> usize {
+        ((x / self.cell_w).floor() as i32).rem_euclid(self.cols as i32) as usize
+    }
+
+    fn row_of(&self, y: f32) -> usize {
+        ((y / self.cell_h).floor() as i32).rem_euclid(self.rows as i32) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1280x720 with 100-unit cells doesn't divide evenly, so this also covers
+    // the stretched last column/row.
+    const BOUNDS: Vec2 = Vec2::new(1280.0, 720.0);
+
+    fn found(item: Vec2, query: Vec2) -> bool {
+        let mut grid = SpatialHash::new(100.0);
+        grid.rebuild(BOUNDS, [(7, item, 10.0)].into_iter());
+        let mut out = vec![];
+        grid.query(query, 10.0, &mut out);
+        out == [7]
+    }
+
+    #[test]
+    fn query_wraps_across_every_edge() {
+        let (w, h) = (BOUNDS.x, BOUNDS.y);
+        // left <-> right
+        assert!(found(Vec2::new(w - 5.0, 300.0), Vec2::new(5.0, 300.0)));
+        assert!(found(Vec2::new(5.0, 300.0), Vec2::new(w - 5.0, 300.0)));
+        // top <-> bottom
+        assert!(found(Vec2::new(600.0, h - 5.0), Vec2::new(600.0, 5.0)));
+        assert!(found(Vec2::new(600.0, 5.0), Vec2::new(600.0, h - 5.0)));
+        // diagonally across a corner
+        assert!(found(Vec2::new(w - 5.0, h - 5.0), Vec2::new(5.0, 5.0)));
+    }
+
+    #[test]
+    fn far_items_are_not_candidates() {
+        assert!(!found(Vec2::new(640.0, 360.0), Vec2::new(5.0, 5.0)));
+    }
+}
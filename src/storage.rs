@@ -0,0 +1,60 @@
+// Small named text blobs that survive between runs (high scores, settings, ...).
+//
+// Native builds keep each one in a file, saves/<key>.txt in the working directory.
+// Browsers have no file system for us, so the WASM build uses localStorage instead
+// (through the cacaroids plugin in index.html).
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path(key: &str) -> std::path::PathBuf {
+    std::path::Path::new("saves").join(format!("{key}.txt"))
+}
+
+// None if nothing was saved under `key` yet (or it can't be read).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(key: &str) -> Option<String> {
+    std::fs::read_to_string(path(key)).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(key: &str, value: &str) -> Result<(), String> {
+    let path = path(key);
+    let write = || -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, value)
+    };
+    write().map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    // Copies the stored value into `out` if it fits in `out_cap` bytes.
+    // Returns its length either way, or a negative number if there's no such key.
+    fn cacaroids_storage_load(key: *const u8, key_len: u32, out: *mut u8, out_cap: u32) -> i32;
+
+    // Returns false if the browser refused (private mode, storage full, ...).
+    fn cacaroids_storage_save(key: *const u8, key_len: u32, value: *const u8, value_len: u32) -> bool;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(key: &str) -> Option<String> {
+    // First ask how long it is, then fetch it into a buffer of that size
+    let len = unsafe { cacaroids_storage_load(key.as_ptr(), key.len() as u32, std::ptr::null_mut(), 0) };
+    if len < 0 {
+        return None;
+    }
+    let mut bytes = vec![0u8; len as usize];
+    unsafe { cacaroids_storage_load(key.as_ptr(), key.len() as u32, bytes.as_mut_ptr(), len as u32) };
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(key: &str, value: &str) -> Result<(), String> {
+    let ok = unsafe { cacaroids_storage_save(key.as_ptr(), key.len() as u32, value.as_ptr(), value.len() as u32) };
+    if ok {
+        Ok(())
+    } else {
+        Err("the browser refused to store it".to_string())
+    }
+}
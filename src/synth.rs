@@ -0,0 +1,346 @@
+use crate::asteroid::AsteroidSize;
+
+// A tiny sfxr-style synthesizer.
+//
+// Every sound is described by a `Params` set and rendered at start-up into a buffer
+// of mono f32 samples, then wrapped in a WAV header so macroquad can load it.
+// No audio files ship with the game.
+//
+// Rendering is plain arithmetic plus a seeded xorshift for noise. It doesn't touch
+// macroquad, so the same params always produce the same samples, on any machine,
+// with or without a sound card.
+
+pub const SAMPLE_RATE: u32 = 22050;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Waveform {
+    Square,
+    Triangle,
+    Sine,
+    // Sample-and-hold noise: a new random value every 1/freq seconds,
+    // so higher freq = hissier, lower = rumblier.
+    Noise,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Params {
+    pub waveform: Waveform,
+    // Starting pitch in Hz, and how it changes in octaves per second (negative = falls)
+    pub freq: f32,
+    pub slide: f32,
+    // Pitch wobble: depth as a fraction of the pitch, speed in Hz
+    pub vibrato_depth: f32,
+    pub vibrato_speed: f32,
+    // Square waves only: fraction of each cycle spent high
+    pub duty: f32,
+    // Volume envelope in seconds: fade in, hold, fade out
+    pub attack: f32,
+    pub sustain: f32,
+    pub decay: f32,
+    // One-pole low-pass cutoff in Hz (0 = off), and its slide in octaves per second
+    pub lowpass: f32,
+    pub lowpass_slide: f32,
+    pub volume: f32,
+    // Noise seed
+    pub seed: u32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            waveform: Waveform::Square,
+            freq: 440.0,
+            slide: 0.0,
+            vibrato_depth: 0.0,
+            vibrato_speed: 0.0,
+            duty: 0.5,
+            attack: 0.0,
+            sustain: 0.1,
+            decay: 0.1,
+            lowpass: 0.0,
+            lowpass_slide: 0.0,
+            volume: 0.5,
+            seed: 1,
+        }
+    }
+}
+
+impl Params {
+    pub fn duration(&self) -> f32 {
+        self.attack + self.sustain + self.decay
+    }
+}
+
+// Renders one sound into mono samples in -1..1, SAMPLE_RATE per second.
+pub fn render(p: &Params) -> Vec<f32> {
+    let dt = 1.0 / SAMPLE_RATE as f32;
+    let len = (p.duration() * SAMPLE_RATE as f32).round() as usize;
+    let mut out = Vec::with_capacity(len);
+
+    let mut rng = p.seed.max(1);
+    let mut noise = 0.0;
+    let mut phase = 0.0_f32;
+    let mut filtered = 0.0;
+
+    for i in 0..len {
+        let t = i as f32 * dt;
+
+        let vibrato = 1.0 + p.vibrato_depth * (std::f32::consts::TAU * p.vibrato_speed * t).sin();
+        let freq = p.freq * 2.0_f32.powf(p.slide * t) * vibrato;
+        phase += freq * dt;
+        if phase >= 1.0 {
+            phase -= phase.floor();
+            noise = next_noise(&mut rng);
+        }
+
+        let mut sample = match p.waveform {
+            Waveform::Square   => if phase < p.duty { 1.0 } else { -1.0 },
+            Waveform::Triangle => if phase < 0.5 { 4.0 * phase - 1.0 } else { 3.0 - 4.0 * phase },
+            Waveform::Sine     => (std::f32::consts::TAU * phase).sin(),
+            Waveform::Noise    => noise,
+        };
+
+        if p.lowpass > 0.0 {
+            let cutoff = p.lowpass * 2.0_f32.powf(p.lowpass_slide * t);
+            let k = 1.0 - (-std::f32::consts::TAU * cutoff * dt).exp();
+            filtered += k * (sample - filtered);
+            sample = filtered;
+        }
+
+        out.push(sample * envelope(p, t) * p.volume);
+    }
+    out
+}
+
+fn envelope(p: &Params, t: f32) -> f32 {
+    if t < p.attack {
+        t / p.attack
+    } else if t < p.attack + p.sustain {
+        1.0
+    } else if p.decay > 0.0 {
+        (1.0 - (t - p.attack - p.sustain) / p.decay).max(0.0)
+    } else {
+        0.0
+    }
+}
+
+// xorshift32, mapped to -1..1
+fn next_noise(state: &mut u32) -> f32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    *state as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+// Plays `params` once per note, each note starting `seconds` after the previous one.
+// A note's frequency replaces params.freq; 0.0 is a rest. Notes can ring into the next.
+pub fn render_notes(params: &Params, notes: &[(f32, f32)]) -> Vec<f32> {
+    let total: f32 = notes.iter().map(|&(_, seconds)| seconds).sum();
+    let mut out = vec![0.0; (total * SAMPLE_RATE as f32).round() as usize];
+    let mut start = 0.0;
+    for &(freq, seconds) in notes {
+        if freq > 0.0 {
+            let note = render(&Params { freq, ..*params });
+            mix_into(&mut out, &note, (start * SAMPLE_RATE as f32).round() as usize);
+        }
+        start += seconds;
+    }
+    out
+}
+
+// Adds `src` onto `dst` starting at sample `offset`. Whatever runs past the end is dropped.
+pub fn mix_into(dst: &mut [f32], src: &[f32], offset: usize) {
+    for (d, s) in dst.iter_mut().skip(offset).zip(src) {
+        *d += s;
+    }
+}
+
+// Wraps samples in a 16-bit mono PCM WAV file, for macroquad's load_sound_from_bytes.
+pub fn to_wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());              // chunk size
+    bytes.extend_from_slice(&1u16.to_le_bytes());               // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes());               // mono
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());  // bytes per second
+    bytes.extend_from_slice(&2u16.to_le_bytes());               // bytes per frame
+    bytes.extend_from_slice(&16u16.to_le_bytes());              // bits per sample
+
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for &sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+// --- THE GAME'S SOUNDS ---
+
+// Falling square blip
+pub fn fire() -> Params {
+    Params {
+        freq: 900.0,
+        slide: -6.0,
+        duty: 0.3,
+        sustain: 0.02,
+        decay: 0.12,
+        volume: 0.35,
+        ..Default::default()
+    }
+}
+
+// Filtered noise bursts. Bigger asteroids rumble lower and longer.
+pub fn explosion(size: AsteroidSize) -> Params {
+    let (freq, lowpass, length) = match size {
+        AsteroidSize::Big    => (1500.0, 1200.0, 1.0),
+        AsteroidSize::Medium => (3000.0, 2200.0, 0.65),
+        AsteroidSize::Small  => (6000.0, 4000.0, 0.4),
+    };
+    Params {
+        waveform: Waveform::Noise,
+        freq,
+        slide: -1.0,
+        sustain: length * 0.1,
+        decay: length * 0.9,
+        lowpass,
+        lowpass_slide: -2.5,
+        volume: 0.8,
+        seed: 0xD1E5,
+        ..Default::default()
+    }
+}
+
+// Loops: no fade in or out, so the ends meet
+pub fn thrust() -> Params {
+    Params {
+        waveform: Waveform::Noise,
+        freq: 2000.0,
+        sustain: 1.0,
+        decay: 0.0,
+        lowpass: 350.0,
+        volume: 0.5,
+        seed: 0x7E57,
+        ..Default::default()
+    }
+}
+
+// Warbling tone. 0.5 s is a whole number of vibrato cycles, so it loops cleanly.
+pub fn saucer() -> Params {
+    Params {
+        waveform: Waveform::Sine,
+        freq: 520.0,
+        vibrato_depth: 0.15,
+        vibrato_speed: 8.0,
+        sustain: 0.5,
+        decay: 0.0,
+        volume: 0.3,
+        ..Default::default()
+    }
+}
+
+// Rising arpeggio
+pub fn extra_life() -> Vec<f32> {
+    let note = Params { sustain: 0.05, decay: 0.05, volume: 0.3, ..Default::default() };
+    render_notes(&note, &[(523.25, 0.09), (659.25, 0.09), (783.99, 0.09), (1046.5, 0.2)])
+}
+
+// Slow descending notes
+pub fn game_over() -> Vec<f32> {
+    let note = Params { waveform: Waveform::Triangle, sustain: 0.05, decay: 0.3, volume: 0.45, ..Default::default() };
+    render_notes(&note, &[(392.0, 0.22), (349.23, 0.22), (311.13, 0.22), (261.63, 0.6)])
+}
+
+// An 8 second loop: the classic two-note heartbeat bass under a quiet arpeggio.
+pub fn music() -> Vec<f32> {
+    let beat = 0.5;
+    let bass = Params { waveform: Waveform::Triangle, sustain: 0.05, decay: 0.25, volume: 0.5, ..Default::default() };
+    let bass_notes: Vec<(f32, f32)> = (0..16).map(|i| (if i % 2 == 0 { 55.0 } else { 51.91 }, beat)).collect();
+    let mut out = render_notes(&bass, &bass_notes);
+
+    let arp = Params { sustain: 0.02, decay: 0.1, volume: 0.08, ..Default::default() };
+    let pattern = [220.0, 261.63, 329.63, 261.63, 207.65, 246.94, 311.13, 246.94];
+    let arp_notes: Vec<(f32, f32)> = (0..32)
+        .map(|i| {
+            let octave = if (i / 8) % 2 == 1 { 2.0 } else { 1.0 };
+            (pattern[i % 8] * octave, beat / 2.0)
+        })
+        .collect();
+    mix_into(&mut out, &render_notes(&arp, &arp_notes), 0);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn render_is_deterministic() {
+        assert_eq!(render(&fire()), render(&fire()));
+        assert_eq!(render(&explosion(AsteroidSize::Big)), render(&explosion(AsteroidSize::Big)));
+    }
+
+    #[test]
+    fn render_length_matches_duration() {
+        for p in [fire(), explosion(AsteroidSize::Medium), thrust(), saucer()] {
+            let expected = (p.duration() * SAMPLE_RATE as f32).round() as usize;
+            assert_eq!(render(&p).len(), expected);
+        }
+    }
+
+    #[test]
+    fn samples_stay_in_range() {
+        let sounds = [
+            fire(),
+            explosion(AsteroidSize::Big),
+            explosion(AsteroidSize::Medium),
+            explosion(AsteroidSize::Small),
+            thrust(),
+            saucer(),
+        ];
+        for p in sounds {
+            assert!(render(&p).iter().all(|s| (-1.0..=1.0).contains(s)));
+        }
+    }
+
+    // Zero crossings per second over the first `seconds`: a rough pitch for noise
+    fn crossing_rate(samples: &[f32], seconds: f32) -> f32 {
+        let window = &samples[..(seconds * SAMPLE_RATE as f32) as usize];
+        let crossings = window.windows(2).filter(|w| (w[0] < 0.0) != (w[1] < 0.0)).count();
+        crossings as f32 / seconds
+    }
+
+    #[test]
+    fn smaller_explosions_are_higher_pitched() {
+        let rate = |size| crossing_rate(&render(&explosion(size)), 0.2);
+        let (big, medium, small) = (rate(AsteroidSize::Big), rate(AsteroidSize::Medium), rate(AsteroidSize::Small));
+        assert!(big < medium, "big {big} vs medium {medium}");
+        assert!(medium < small, "medium {medium} vs small {small}");
+    }
+
+    #[test]
+    fn wav_header() {
+        let samples = render(&fire());
+        let wav = to_wav(&samples);
+        let data_len = samples.len() as u32 * 2;
+
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32_at(&wav, 4), 36 + data_len);
+        assert_eq!(&wav[8..12], b"WAVE");
+        assert_eq!(&wav[12..16], b"fmt ");
+        assert_eq!(u32_at(&wav, 24), SAMPLE_RATE);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32_at(&wav, 40), data_len);
+        assert_eq!(wav.len(), 44 + data_len as usize);
+    }
+}
@@ -0,0 +1,130 @@
+// Fixed-timestep bookkeeping: real frame time goes in, whole simulation steps
+// of `dt` come out, and whatever is left over is how far to interpolate when drawing.
+//
+// A hit-stop freezes it for a moment: no time reaches the simulation until it
+// runs out. Steps still owed from the frame the freeze started in are dropped
+// rather than saved up, so the picture holds still instead of being drawn
+// extrapolated past the last step.
+pub struct FixedStep {
+    dt: f32,
+    // Real time that has passed but hasn't been simulated yet (always < dt between frames)
+    accumulator: f32,
+    // Seconds of hit-stop left
+    hit_stop: f32,
+}
+
+impl FixedStep {
+    pub fn new(dt: f32) -> Self {
+        Self { dt, accumulator: 0.0, hit_stop: 0.0 }
+    }
+
+    // Adds one frame's worth of real time. While frozen it goes to the
+    // hit-stop instead, and no steps come out of this frame.
+    pub fn add_frame(&mut self, frame_time: f32) {
+        if self.hit_stop > 0.0 {
+            self.hit_stop -= frame_time;
+        } else {
+            self.accumulator += frame_time;
+        }
+    }
+
+    // Takes the next step of this frame, if there's time for one.
+    // Call in a loop after add_frame().
+    pub fn next_step(&mut self) -> bool {
+        if self.hit_stop > 0.0 || self.accumulator < self.dt {
+            return false;
+        }
+        self.accumulator -= self.dt;
+        true
+    }
+
+    // Freezes for `seconds`. Overlapping freezes don't stack; the longest wins.
+    pub fn hit_stop(&mut self, seconds: f32) {
+        if seconds > 0.0 {
+            self.hit_stop = self.hit_stop.max(seconds);
+            self.accumulator %= self.dt;
+        }
+    }
+
+    // Ends any freeze straight away
+    pub fn thaw(&mut self) {
+        self.hit_stop = 0.0;
+    }
+
+    // Throws away time not yet simulated, e.g. when the run ends mid-frame
+    pub fn drop_pending(&mut self) {
+        self.accumulator = 0.0;
+    }
+
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+        self.hit_stop = 0.0;
+    }
+
+    // How far we are between the last step and the next one, 0..1
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.dt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 120.0;
+
+    // Runs one frame, freezing after `freeze_after` steps. Returns the steps taken.
+    fn frame(clock: &mut FixedStep, frame_time: f32, freeze_after: Option<usize>) -> usize {
+        clock.add_frame(frame_time);
+        let mut steps = 0;
+        while clock.next_step() {
+            steps += 1;
+            if Some(steps) == freeze_after {
+                clock.hit_stop(0.2);
+            }
+        }
+        steps
+    }
+
+    #[test]
+    fn steps_fit_the_frame() {
+        let mut clock = FixedStep::new(DT);
+        assert_eq!(frame(&mut clock, DT * 3.5, None), 3);
+        assert!(clock.alpha() < 1.0);
+        assert_eq!(frame(&mut clock, DT * 0.5, None), 1);
+    }
+
+    #[test]
+    fn hit_stop_mid_frame_drops_the_rest_of_the_frame() {
+        let mut clock = FixedStep::new(DT);
+        // A slow frame with room for 5 steps; the freeze starts after the 2nd
+        assert_eq!(frame(&mut clock, DT * 5.5, Some(2)), 2);
+        assert!(clock.alpha() >= 0.0 && clock.alpha() < 1.0);
+
+        // Frozen frames run nothing and keep the picture where it is
+        let alpha = clock.alpha();
+        for _ in 0..10 {
+            assert_eq!(frame(&mut clock, 1.0 / 60.0, None), 0);
+            assert_eq!(clock.alpha(), alpha);
+        }
+
+        // Once it's over, time flows again, and the owed steps never show up
+        for _ in 0..5 {
+            frame(&mut clock, 1.0 / 60.0, None);
+        }
+        assert!(clock.alpha() < 1.0);
+        assert!(frame(&mut clock, 1.0 / 60.0, None) <= 2);
+    }
+
+    #[test]
+    fn longest_freeze_wins() {
+        let mut clock = FixedStep::new(DT);
+        clock.hit_stop(0.2);
+        clock.hit_stop(0.05);
+        clock.add_frame(0.1);
+        assert!(!clock.next_step());
+        clock.add_frame(0.11);
+        clock.add_frame(DT);
+        assert!(clock.next_step());
+    }
+}
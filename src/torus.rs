@@ -0,0 +1,111 @@
+use macroquad::math::Vec2;
+
+// Geometry on the wrap-around playfield.
+//
+// The playfield is a torus: leaving one edge brings you back on the opposite one.
+// So the shortest way from A to B may go across an edge, and two things near
+// opposite edges can be right next to each other.
+
+// Shortest offset from `from` to `to` on a playfield of size `bounds`,
+// i.e. `to - from`, but going across an edge whenever that's shorter.
+pub fn delta(from: Vec2, to: Vec2, bounds: Vec2) -> Vec2 {
+    let mut d = to - from;
+    if d.x >  bounds.x / 2.0 { d.x -= bounds.x; }
+    if d.x < -bounds.x / 2.0 { d.x += bounds.x; }
+    if d.y >  bounds.y / 2.0 { d.y -= bounds.y; }
+    if d.y < -bounds.y / 2.0 { d.y += bounds.y; }
+    d
+}
+
+// Distance between two points taking the wrap into account.
+// Use this instead of Vec2::distance for anything on the playfield.
+pub fn distance(a: Vec2, b: Vec2, bounds: Vec2) -> f32 {
+    delta(a, b, bounds).length()
+}
+
+// Brings a position that has crossed an edge back onto the playfield.
+// Wraps by exactly the playfield size (not by snapping to the opposite edge),
+// so the wrap happens when the centre crosses and motion stays continuous.
+pub fn wrap(pos: Vec2, bounds: Vec2) -> Vec2 {
+    Vec2::new(pos.x.rem_euclid(bounds.x), pos.y.rem_euclid(bounds.y))
+}
+
+// Calls `draw` with every place a sprite of `radius` centred at `pos` is visible:
+// the position itself, plus a ghost on the opposite side for each edge it overlaps
+// (up to four copies in a corner). Drawing all of them makes things slide across
+// edges instead of popping from one side to the other.
+pub fn for_each_ghost(pos: Vec2, radius: f32, bounds: Vec2, mut draw: impl FnMut(Vec2)) {
+    let dx = if pos.x < radius {
+        bounds.x
+    } else if pos.x > bounds.x - radius {
+        -bounds.x
+    } else {
+        0.0
+    };
+    let dy = if pos.y < radius {
+        bounds.y
+    } else if pos.y > bounds.y - radius {
+        -bounds.y
+    } else {
+        0.0
+    };
+
+    draw(pos);
+    if dx != 0.0 { draw(pos + Vec2::new(dx, 0.0)); }
+    if dy != 0.0 { draw(pos + Vec2::new(0.0, dy)); }
+    if dx != 0.0 && dy != 0.0 { draw(pos + Vec2::new(dx, dy)); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: Vec2 = Vec2::new(1280.0, 720.0);
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        (a - b).length() < 1e-3
+    }
+
+    #[test]
+    fn delta_goes_across_each_edge() {
+        // Right edge to left edge and back
+        assert!(close(delta(Vec2::new(1270.0, 300.0), Vec2::new(10.0, 300.0), BOUNDS), Vec2::new(20.0, 0.0)));
+        assert!(close(delta(Vec2::new(10.0, 300.0), Vec2::new(1270.0, 300.0), BOUNDS), Vec2::new(-20.0, 0.0)));
+        // Bottom edge to top edge and back
+        assert!(close(delta(Vec2::new(600.0, 715.0), Vec2::new(600.0, 5.0), BOUNDS), Vec2::new(0.0, 10.0)));
+        assert!(close(delta(Vec2::new(600.0, 5.0), Vec2::new(600.0, 715.0), BOUNDS), Vec2::new(0.0, -10.0)));
+        // Nothing to gain from wrapping: the plain offset
+        assert!(close(delta(Vec2::new(100.0, 100.0), Vec2::new(400.0, 300.0), BOUNDS), Vec2::new(300.0, 200.0)));
+    }
+
+    #[test]
+    fn distance_across_a_corner() {
+        let d = distance(Vec2::new(1277.0, 716.0), Vec2::new(3.0, 4.0), BOUNDS);
+        assert!((d - 10.0).abs() < 1e-3);
+        // Symmetric
+        assert_eq!(d, distance(Vec2::new(3.0, 4.0), Vec2::new(1277.0, 716.0), BOUNDS));
+    }
+
+    #[test]
+    fn wrap_negative_and_over_bounds() {
+        assert!(close(wrap(Vec2::new(-10.0, -20.0), BOUNDS), Vec2::new(1270.0, 700.0)));
+        assert!(close(wrap(Vec2::new(1290.0, 730.0), BOUNDS), Vec2::new(10.0, 10.0)));
+        assert!(close(wrap(Vec2::new(-1290.0, 1450.0), BOUNDS), Vec2::new(1270.0, 10.0)));
+        // Already inside: untouched
+        assert_eq!(wrap(Vec2::new(640.0, 360.0), BOUNDS), Vec2::new(640.0, 360.0));
+        // Exactly on the far edge is the same place as the near one
+        assert_eq!(wrap(Vec2::new(1280.0, 0.0), BOUNDS), Vec2::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn ghosts_for_edges_and_corners() {
+        let ghosts = |pos: Vec2| {
+            let mut out = vec![];
+            for_each_ghost(pos, 20.0, BOUNDS, |p| out.push(p));
+            out
+        };
+        assert_eq!(ghosts(Vec2::new(640.0, 360.0)), [Vec2::new(640.0, 360.0)]);
+        assert_eq!(ghosts(Vec2::new(10.0, 360.0)), [Vec2::new(10.0, 360.0), Vec2::new(1290.0, 360.0)]);
+        assert_eq!(ghosts(Vec2::new(1275.0, 715.0)).len(), 4);
+    }
+}
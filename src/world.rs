@@ -0,0 +1,585 @@
+use macroquad::math::{vec2, Vec2};
+use macroquad::rand::RandGenerator;
+use crate::player::Player;
+use crate::bullet::{Bullet, Faction};
+use crate::asteroid::{Asteroid, AsteroidSize};
+use crate::saucer::{Saucer, SaucerSize};
+use crate::spatial::SpatialHash;
+use crate::torus;
+use crate::rules::{Rules, SizeMix, Wave};
+
+// Seconds between losing a ship and the next one being allowed to appear
+const RESPAWN_DELAY: f32 = 1.5;
+
+// How many random spots a safe hyperspace jump tries before settling for the last one
+const HYPERSPACE_ATTEMPTS: usize = 32;
+
+// Chance that a saucer is the small, accurate kind: grows with score between these bounds.
+// The chance reaches its max at SMALL_SAUCER_SCORE points.
+const SMALL_SAUCER_CHANCE: (f32, f32) = (0.1, 0.8);
+const SMALL_SAUCER_SCORE: f32 = 20_000.0;
+
+// Breather between clearing a wave and the next one spawning, in seconds
+const INTERMISSION: f32 = 2.5;
+
+// Minimum distance from the player where asteroids can spawn.
+// Prevents instant death at game start.
+const SAFE_RADIUS: f32 = 150.0;
+
+// What the player is asking for during one simulation step.
+// The world never reads the keyboard itself — whoever drives it fills this in
+// (the macroquad layer from real keys, a test from a script, etc).
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Input {
+    // How hard to turn: -1 is full speed left, 1 full speed right.
+    // Keys only ever give -1, 0 or 1; an analog stick anything in between.
+    pub turn: f32,
+    pub thrust: bool,
+    pub fire: bool,
+    pub hyperspace: bool,
+}
+
+// Things that happened during a step that the presentation layer may want to react to
+// (HUD flashes, sounds, particles, ...). The world only records them; Game drains them after each step.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WorldEvent {
+    ExtraLife,
+    Fired { faction: Faction },
+    // An asteroid was hit (by a bullet or a saucer) at `pos`
+    AsteroidDestroyed { pos: Vec2, size: AsteroidSize },
+    SaucerDestroyed { pos: Vec2 },
+    PlayerDied { pos: Vec2 },
+}
+
+// The game can be in one of these states.
+// This drives what gets updated and what gets drawn.
+// Clearing a wave doesn't end the run — the next wave just follows.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameState {
+    Playing,
+    GameOver,
+}
+
+// The whole simulation: every entity, the score and the round state.
+// Plain data + logic, no textures, no window, no global input or RNG,
+// so it can be stepped from a unit test or a server just as well as from the game.
+pub struct World {
+    // Size of the playfield. Everything wraps around at these edges.
+    pub bounds: Vec2,
+    pub player: Player,
+    pub bullets: Vec<Bullet>,
+    pub asteroids: Vec<Asteroid>,
+    pub saucers: Vec<Saucer>,
+    pub state: GameState,
+    pub score: u32,
+
+    // Ships left, including the one currently in play
+    pub lives: u32,
+
+    // Seconds until the next ship may respawn (only counts down while the player is dead).
+    // Once it runs out the ship still waits for the centre to be clear.
+    respawn_timer: f32,
+
+    // Seconds until the next saucer shows up
+    saucer_timer: f32,
+
+    // Current wave number, starting at 1
+    pub wave: u32,
+
+    // Seconds left until the next wave spawns. Zero while a wave is in progress.
+    pub intermission: f32,
+
+    pub rules: Rules,
+
+    // Events raised since the last time they were drained
+    pub events: Vec<WorldEvent>,
+
+    // Broadphase over the asteroids, rebuilt every step, plus a reusable
+    // buffer for its query results.
+    grid: SpatialHash,
+    candidates: Vec<usize>,
+
+    rng: RandGenerator,
+}
+
+impl World {
+    pub fn new(bounds: Vec2, rules: Rules, rng: RandGenerator) -> Self {
+        let player = Player::new(bounds / 2.0);
+
+        // Spawn the first wave avoiding the player's starting position
+        let asteroids = Self::spawn_asteroids(&rules.waves.wave(1), player.pos, bounds, &rng);
+
+        Self {
+            bounds,
+            player,
+            bullets: vec![],
+            asteroids,
+            saucers: vec![],
+            state: GameState::Playing,
+            score: 0,
+            lives: rules.starting_lives,
+            respawn_timer: 0.0,
+            saucer_timer: Self::next_saucer_delay(&rules, &rng),
+            wave: 1,
+            intermission: 0.0,
+            rules,
+            events: vec![],
+            grid: SpatialHash::new(AsteroidSize::Big.radius() * 2.0),
+            candidates: vec![],
+            rng,
+        }
+    }
+
+    // Spawns the asteroids of `wave` at random positions,
+    // retrying each one until it's far enough from `avoid` (measured across edges too).
+    fn spawn_asteroids(wave: &Wave, avoid: Vec2, bounds: Vec2, rng: &RandGenerator) -> Vec<Asteroid> {
+        (0..wave.count).map(|_| {
+            let size = Self::pick_size(&wave.size_mix, rng);
+            loop {
+                let pos = vec2(
+                    rng.gen_range(0.0, bounds.x),
+                    rng.gen_range(0.0, bounds.y),
+                );
+                if torus::distance(pos, avoid, bounds) > SAFE_RADIUS {
+                    return Asteroid::new(pos, size, wave.speed_scale, rng);
+                }
+                // If too close, loop again and try a new random position
+            }
+        }).collect()
+    }
+
+    // Weighted random pick from the wave's size mix.
+    // An all-zero mix falls back to big asteroids.
+    fn pick_size(mix: &SizeMix, rng: &RandGenerator) -> AsteroidSize {
+        let total = mix.big + mix.medium + mix.small;
+        if total == 0 {
+            return AsteroidSize::Big;
+        }
+        let roll = rng.gen_range(0, total);
+        if roll < mix.big {
+            AsteroidSize::Big
+        } else if roll < mix.big + mix.medium {
+            AsteroidSize::Medium
+        } else {
+            AsteroidSize::Small
+        }
+    }
+
+    // Adds points and hands out any extra lives the new score has earned.
+    fn add_score(&mut self, points: u32) {
+        let old = self.score;
+        self.score += points;
+
+        let earned = self.rules.extra_lives.lives_earned(old, self.score);
+        for _ in 0..earned {
+            self.lives += 1;
+            self.events.push(WorldEvent::ExtraLife);
+        }
+    }
+
+    // The player's ship was destroyed. Ends the run on the last life,
+    // otherwise starts the respawn countdown.
+    fn lose_life(&mut self) {
+        self.player.alive = false;
+        self.events.push(WorldEvent::PlayerDied { pos: self.player.pos });
+        self.lives = self.lives.saturating_sub(1);
+        if self.lives == 0 {
+            self.state = GameState::GameOver;
+        } else {
+            self.respawn_timer = RESPAWN_DELAY;
+        }
+    }
+
+    // Jumps the player to a random spot. With `hyperspace_safe` the spot has to be
+    // clear of asteroids (same SAFE_RADIUS rule as spawning); otherwise anything goes.
+    // There's a chance the ship doesn't survive re-entry.
+    fn hyperspace(&mut self) {
+        let mut pos = Vec2::ZERO;
+        for _ in 0..HYPERSPACE_ATTEMPTS {
+            pos = vec2(
+                self.rng.gen_range(0.0, self.bounds.x),
+                self.rng.gen_range(0.0, self.bounds.y),
+            );
+            let clear = self.asteroids.iter()
+                .filter(|a| a.alive)
+                .all(|a| torus::distance(a.pos, pos, self.bounds) > SAFE_RADIUS + a.radius());
+            if !self.rules.hyperspace_safe || clear {
+                break;
+            }
+        }
+        self.player.teleport(pos, self.rules.hyperspace_cooldown);
+
+        if self.rng.gen_range(0.0_f32, 1.0) < self.rules.hyperspace_failure_chance {
+            self.lose_life();
+        }
+    }
+
+    // Random wait before the next saucer, around the rules' saucer_interval.
+    fn next_saucer_delay(rules: &Rules, rng: &RandGenerator) -> f32 {
+        rules.saucer_interval * rng.gen_range(0.7, 1.3)
+    }
+
+    // Spawns saucers on a timer while a wave is in progress (one at a time),
+    // moves them, and lets them shoot.
+    fn update_saucers(&mut self, dt: f32) {
+        if self.saucers.is_empty() && !self.asteroids.is_empty() {
+            self.saucer_timer -= dt;
+            if self.saucer_timer <= 0.0 {
+                self.saucer_timer = Self::next_saucer_delay(&self.rules, &self.rng);
+
+                let (min, max) = SMALL_SAUCER_CHANCE;
+                let small_chance = (self.score as f32 / SMALL_SAUCER_SCORE).clamp(min, max);
+                let size = if self.rng.gen_range(0.0_f32, 1.0) < small_chance {
+                    SaucerSize::Small
+                } else {
+                    SaucerSize::Large
+                };
+                self.saucers.push(Saucer::new(size, self.bounds, &self.rng));
+            }
+        }
+
+        for s in self.saucers.iter_mut() {
+            if s.update(dt, self.bounds, &self.rng) {
+                // Start the shot just outside the hull
+                let dir = s.aim(self.player.pos, self.score, self.bounds, &self.rng);
+                let pos = s.pos + dir * (s.radius() + 6.0);
+                self.bullets.push(Bullet::new(pos, dir, Faction::Saucer));
+                self.events.push(WorldEvent::Fired { faction: Faction::Saucer });
+            }
+        }
+    }
+
+    // Refills the broadphase grid with the current asteroid positions.
+    fn rebuild_grid(&mut self) {
+        let items = self.asteroids.iter()
+            .enumerate()
+            .filter(|(_, a)| a.alive)
+            .map(|(i, a)| (i, a.pos, a.radius()));
+        self.grid.rebuild(self.bounds, items);
+    }
+
+    // Brings the player back in the centre once the delay is over and no asteroid
+    // is within SAFE_RADIUS of it — same idea as the safe spawn for asteroids.
+    // All distances here and below are toroidal (see torus.rs), so things overlapping
+    // across a screen edge count as touching.
+    fn try_respawn(&mut self, dt: f32) {
+        self.respawn_timer = (self.respawn_timer - dt).max(0.0);
+        if self.respawn_timer > 0.0 {
+            return;
+        }
+
+        let centre = self.bounds / 2.0;
+        let clear = self.asteroids.iter()
+            .filter(|a| a.alive)
+            .all(|a| torus::distance(a.pos, centre, self.bounds) > SAFE_RADIUS + a.radius());
+        if clear {
+            self.player.respawn(centre, self.rules.invulnerable_time);
+        }
+    }
+
+    // Advances the simulation by exactly `dt` seconds.
+    // Handles player input, physics, and collision detection.
+    // Does nothing once the round is over.
+    pub fn step(&mut self, input: &Input, dt: f32) {
+        if self.state != GameState::Playing {
+            return;
+        }
+
+        // --- PLAYER UPDATE ---
+        // player.update() returns Some(pos) if the player fired a bullet this step
+        if self.player.alive {
+            if let Some(bullet_pos) = self.player.update(input, dt, self.bounds) {
+                // Compute the forward direction from the player's current rotation
+                let dir = Vec2::from_angle(self.player.rotation - std::f32::consts::FRAC_PI_2);
+                self.bullets.push(Bullet::new(bullet_pos, dir, Faction::Player));
+                self.events.push(WorldEvent::Fired { faction: Faction::Player });
+            }
+            if input.hyperspace && self.player.hyperspace_cooldown <= 0.0 {
+                self.hyperspace();
+                if self.state == GameState::GameOver {
+                    return;
+                }
+            }
+        } else {
+            self.try_respawn(dt);
+        }
+
+        // --- BULLET UPDATE ---
+        for b in self.bullets.iter_mut() {
+            b.update(dt, self.bounds);
+        }
+
+        // --- ASTEROID UPDATE ---
+        for a in self.asteroids.iter_mut() {
+            a.update(dt, self.bounds);
+        }
+
+        // --- SAUCER UPDATE ---
+        self.update_saucers(dt);
+
+        // --- BROADPHASE ---
+        // Every asteroid check below asks the grid for nearby asteroids
+        // instead of looping over all of them.
+        self.rebuild_grid();
+
+        // --- BULLET <-> ASTEROID COLLISIONS ---
+        // We collect new child asteroids separately to avoid mutating
+        // the vec while iterating over it (Rust won't allow that).
+        // Bullets from either faction break asteroids, but only the player's score.
+        let mut new_asteroids: Vec<Asteroid> = vec![];
+        let mut points = 0;
+
+        for b in self.bullets.iter_mut() {
+            if !b.alive { continue; } // skip already-dead bullets
+
+            self.grid.query(b.pos, b.radius(), &mut self.candidates);
+            for &i in self.candidates.iter() {
+                let a = &mut self.asteroids[i];
+                if !a.alive { continue; } // skip already-dead asteroids
+
+                // Simple circle-circle collision check
+                if torus::distance(b.pos, a.pos, self.bounds) < b.radius() + a.radius() {
+                    b.alive = false; // bullet is consumed
+                    a.alive = false; // asteroid is destroyed
+                    self.events.push(WorldEvent::AsteroidDestroyed { pos: a.pos, size: a.size });
+                    if b.faction == Faction::Player {
+                        points += a.size.score();
+                    }
+
+                    // Split into 2 smaller asteroids (or nothing if already Small)
+                    let children = a.split(&self.rng);
+                    new_asteroids.extend(children);
+                    break; // a spent bullet can't hit anything else
+                }
+            }
+        }
+
+        // --- PLAYER BULLET <-> SAUCER COLLISIONS ---
+        // Saucers never shoot each other down
+        for b in self.bullets.iter_mut() {
+            if !b.alive || b.faction != Faction::Player { continue; }
+
+            for s in self.saucers.iter_mut() {
+                if !s.alive { continue; }
+                if torus::distance(b.pos, s.pos, self.bounds) < b.radius() + s.radius() {
+                    b.alive = false;
+                    s.alive = false;
+                    points += s.size.score();
+                    self.events.push(WorldEvent::SaucerDestroyed { pos: s.pos });
+                }
+            }
+        }
+
+        // --- SAUCER <-> ASTEROID COLLISIONS ---
+        // Saucers don't dodge: flying into a poop destroys both (no points)
+        for s in self.saucers.iter_mut() {
+            if !s.alive { continue; }
+
+            self.grid.query(s.pos, s.radius(), &mut self.candidates);
+            for &i in self.candidates.iter() {
+                let a = &mut self.asteroids[i];
+                if !a.alive { continue; }
+                if torus::distance(s.pos, a.pos, self.bounds) < s.radius() + a.radius() {
+                    s.alive = false;
+                    a.alive = false;
+                    self.events.push(WorldEvent::SaucerDestroyed { pos: s.pos });
+                    self.events.push(WorldEvent::AsteroidDestroyed { pos: a.pos, size: a.size });
+                    new_asteroids.extend(a.split(&self.rng));
+                    break;
+                }
+            }
+        }
+
+        // Now it's safe to add the new asteroids.
+        // The grid has to learn about them before the player check below.
+        if !new_asteroids.is_empty() {
+            self.asteroids.extend(new_asteroids);
+            self.rebuild_grid();
+        }
+        self.add_score(points);
+
+        // --- PLAYER COLLISIONS ---
+        // Asteroids, saucer bullets and saucers themselves all cost a life.
+        // A freshly respawned (blinking) player can't be hit.
+        if self.player.alive && !self.player.is_invulnerable() {
+            let pos = self.player.pos;
+            let radius = self.player.radius();
+            let mut hit = false;
+
+            self.grid.query(pos, radius, &mut self.candidates);
+            for &i in self.candidates.iter() {
+                let a = &self.asteroids[i];
+                if a.alive && torus::distance(pos, a.pos, self.bounds) < radius + a.radius() {
+                    hit = true;
+                    break;
+                }
+            }
+
+            for b in self.bullets.iter_mut() {
+                if b.alive && b.faction == Faction::Saucer && torus::distance(pos, b.pos, self.bounds) < radius + b.radius() {
+                    b.alive = false;
+                    hit = true;
+                }
+            }
+
+            // Ramming a saucer takes it down with you, and still scores it
+            let mut rammed = 0;
+            for s in self.saucers.iter_mut() {
+                if s.alive && torus::distance(pos, s.pos, self.bounds) < radius + s.radius() {
+                    s.alive = false;
+                    rammed += s.size.score();
+                    self.events.push(WorldEvent::SaucerDestroyed { pos: s.pos });
+                    hit = true;
+                }
+            }
+            self.add_score(rammed);
+
+            if hit {
+                self.lose_life();
+                if self.state == GameState::GameOver {
+                    return; // no need to check further
+                }
+            }
+        }
+
+        // --- CLEANUP ---
+        // Remove entities that were marked dead this step.
+        // retain() keeps only elements where the closure returns true.
+        self.bullets.retain(|b| b.alive);
+        self.asteroids.retain(|a| a.alive);
+        self.saucers.retain(|s| s.alive);
+
+        // --- WAVE PROGRESSION ---
+        // Once every asteroid (split children included) is gone, wait out a short
+        // intermission, then bring in the next, harder wave.
+        if self.asteroids.is_empty() {
+            if self.intermission <= 0.0 {
+                self.intermission = INTERMISSION;
+            }
+            self.intermission -= dt;
+            if self.intermission <= 0.0 {
+                self.intermission = 0.0;
+                self.wave += 1;
+                let wave = self.rules.waves.wave(self.wave);
+                self.asteroids = Self::spawn_asteroids(&wave, self.player.pos, self.bounds, &self.rng);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn seeded_world(seed: u64) -> World {
+        let rng = RandGenerator::new();
+        rng.srand(seed);
+        World::new(vec2(1280.0, 720.0), Rules::default(), rng)
+    }
+
+    // A world with no asteroids or saucers of its own, so a test can place exactly
+    // what it needs. The player sits in the centre as usual.
+    fn empty_world(seed: u64) -> World {
+        let mut world = seeded_world(seed);
+        world.asteroids.clear();
+        world.saucer_timer = f32::INFINITY;
+        world
+    }
+
+    // A player bullet sitting right on top of a fresh asteroid of `size`
+    fn shoot(world: &mut World, size: AsteroidSize) {
+        let pos = vec2(200.0, 200.0);
+        world.asteroids.push(Asteroid::new(pos, size, 1.0, &world.rng));
+        world.bullets.push(Bullet::new(pos, vec2(1.0, 0.0), Faction::Player));
+        world.step(&Input::default(), DT);
+    }
+
+    #[test]
+    fn big_asteroid_splits_into_two_mediums_and_scores() {
+        let mut world = empty_world(1);
+        shoot(&mut world, AsteroidSize::Big);
+
+        assert_eq!(world.asteroids.len(), 2);
+        assert!(world.asteroids.iter().all(|a| a.size == AsteroidSize::Medium));
+        assert!(world.bullets.is_empty());
+        assert_eq!(world.score, AsteroidSize::Big.score());
+    }
+
+    #[test]
+    fn small_asteroid_leaves_nothing_behind() {
+        let mut world = empty_world(1);
+        shoot(&mut world, AsteroidSize::Small);
+
+        assert!(world.asteroids.is_empty());
+        assert_eq!(world.score, AsteroidSize::Small.score());
+    }
+
+    #[test]
+    fn bullets_hit_asteroids_across_the_screen_edge() {
+        let mut world = empty_world(1);
+        // Asteroid hanging off the right edge, bullet just inside the left one:
+        // 20 units apart across the edge, the whole screen apart without the wrap
+        let asteroid = Asteroid::new(vec2(1270.0, 200.0), AsteroidSize::Big, 1.0, &world.rng);
+        world.asteroids.push(asteroid);
+        world.bullets.push(Bullet::new(vec2(10.0, 200.0), vec2(0.0, 1.0), Faction::Player));
+        world.step(&Input::default(), DT);
+
+        assert!(world.bullets.is_empty());
+        assert_eq!(world.score, AsteroidSize::Big.score());
+        assert!(world.asteroids.iter().all(|a| a.size == AsteroidSize::Medium));
+    }
+
+    #[test]
+    fn hitting_an_asteroid_on_the_last_life_ends_the_run() {
+        let mut world = empty_world(1);
+        world.lives = 1;
+        let pos = world.player.pos;
+        world.asteroids.push(Asteroid::new(pos, AsteroidSize::Big, 1.0, &world.rng));
+        world.step(&Input::default(), DT);
+
+        assert!(!world.player.alive);
+        assert_eq!(world.lives, 0);
+        assert_eq!(world.state, GameState::GameOver);
+        assert!(world.events.contains(&WorldEvent::PlayerDied { pos }));
+    }
+
+    #[test]
+    fn losing_a_life_with_ships_left_keeps_playing() {
+        let mut world = empty_world(1);
+        let pos = world.player.pos;
+        world.asteroids.push(Asteroid::new(pos, AsteroidSize::Big, 1.0, &world.rng));
+        world.step(&Input::default(), DT);
+
+        assert!(!world.player.alive);
+        assert_eq!(world.lives, Rules::default().starting_lives - 1);
+        assert_eq!(world.state, GameState::Playing);
+    }
+
+    // Same seed + same inputs = same game, step for step. Replays depend on this.
+    #[test]
+    fn same_seed_and_inputs_give_the_same_run() {
+        let script = |i: usize| Input {
+            turn: [-1.0, 0.0, 0.5, 1.0][i / 40 % 4],
+            thrust: i % 90 < 30,
+            fire: i.is_multiple_of(7),
+            hyperspace: i % 500 == 499,
+        };
+
+        let mut a = seeded_world(42);
+        let mut b = seeded_world(42);
+        for i in 0..1200 {
+            a.step(&script(i), DT);
+            b.step(&script(i), DT);
+        }
+
+        // The script should actually shoot things, or this proves very little
+        assert!(a.score > 0);
+        let positions = |w: &World| w.asteroids.iter().map(|a| a.pos).collect::<Vec<_>>();
+        assert_eq!(positions(&a), positions(&b));
+        assert_eq!(a.score, b.score);
+        assert_eq!(a.lives, b.lives);
+        assert_eq!(a.player.pos, b.player.pos);
+    }
+}